@@ -2,9 +2,19 @@ import axios from 'axios';
 
 const API_BASE_URL = 'http://localhost:8080';
 
+export interface PollutantObservation {
+  parameter: string;
+  aqi: number;
+  category: string;
+  category_number: number;
+  observed_at: string | null;
+}
+
 export interface AirQualityData {
   aqi: number;
   category: string;
+  dominant_pollutant?: string | null;
+  pollutants?: PollutantObservation[];
   location: string;
   timestamp: string;
 }
@@ -16,6 +26,23 @@ export interface WeatherData {
   wind_direction: number;
 }
 
+export interface UnitLabels {
+  system: 'metric' | 'imperial';
+  temperature: string;
+  wind_speed: string;
+  precipitation: string;
+  humidity: string;
+  wind_direction: string;
+}
+
+// Map layers served as Mapbox Vector Tiles
+export type TileLayer = 'sensors' | 'readings' | 'risk';
+
+export interface ApiErrorBody {
+  code: string;
+  message: string;
+}
+
 export interface EnvironmentalData {
   air_quality: AirQualityData;
   weather: WeatherData;
@@ -23,6 +50,19 @@ export interface EnvironmentalData {
     latitude: number;
     longitude: number;
   };
+  units?: UnitLabels;
+  errors?: ApiErrorBody[];
+}
+
+export interface SensorReading {
+  id: number;
+  sensor_id: number;
+  parameter: string;
+  value: number | null;
+  unit: string | null;
+  aqi: number | null;
+  observed_at: string;
+  source: string;
 }
 
 export interface SensorLocation {
@@ -31,6 +71,34 @@ export interface SensorLocation {
   data_source: string;
   latitude: number;
   longitude: number;
+  latest_readings?: SensorReading[];
+}
+
+export interface SensorDetail {
+  id: number;
+  name: string;
+  data_source: string;
+  latitude: number;
+  longitude: number;
+  external_id: string | null;
+  elevation: number | null; // Meters
+  agency: string | null;
+  parameters: string[];
+  active: boolean;
+  created_at: string | null;
+  updated_at: string | null;
+  decommissioned_at: string | null;
+}
+
+export interface SensorDetailResponse {
+  sensor: SensorDetail;
+  latest_readings: SensorReading[];
+  recent_readings: {
+    hours: number;
+    readings: SensorReading[];
+    count: number;
+  };
+  timestamp: string;
 }
 
 export interface SensorsResponse {
@@ -39,6 +107,60 @@ export interface SensorsResponse {
   timestamp: string;
 }
 
+// Properties of each feature in /api/sensors?format=geojson
+export interface SensorFeatureProperties {
+  id: number;
+  name: string;
+  data_source: string;
+  latest_readings: Record<string, { value: number | null; unit: string | null; aqi: number | null; observed_at: string }>;
+}
+
+export type SensorFeatureCollection = GeoJSON.FeatureCollection<GeoJSON.Point, SensorFeatureProperties>;
+
+export interface PrecipitationCoverage {
+  sensor_id: number | null;
+  total: number;
+  window_days: number;
+  hours_covered: number;
+  hours_expected: number;
+  coverage: number;
+}
+
+export interface DroughtIndex {
+  kbdi: number; // Keetch-Byram Drought Index, 0-800
+  date: string;
+  sensor_id: number | null;
+  source: 'kbdi' | 'default';
+}
+
+export type FuelModel = 'A' | 'C' | 'G' | 'H' | 'L';
+
+export interface NfdrsIndices {
+  fuel_model: FuelModel;
+  erc: number; // Energy Release Component
+  bi: number; // Burning Index
+  sc: number; // Spread Component
+  ic: number; // Ignition Component
+  fuel_moisture: {
+    one_hour: number;
+    ten_hour: number;
+    hundred_hour: number;
+    thousand_hour: number;
+    herbaceous: number;
+    woody: number;
+  };
+}
+
+export interface FireWeather {
+  fosberg: number; // Fosberg Fire Weather Index
+  hot_dry_windy: number; // Hot-Dry-Windy Index from surface conditions
+}
+
+export interface ModelVersion {
+  version: string;
+  trained_at: string | null;
+}
+
 export interface RiskPrediction {
   location: {
     latitude: number;
@@ -55,6 +177,20 @@ export interface RiskPrediction {
     wind_speed: number;
     wind_direction: number;
   };
+  precipitation?: PrecipitationCoverage;
+  drought_index?: DroughtIndex;
+  fire_weather?: FireWeather;
+  nfdrs?: {
+    indices: NfdrsIndices;
+    heavy_fuels: {
+      hundred_hour: number;
+      thousand_hour: number;
+      date: string | null;
+      sensor_id: number | null;
+      source: 'tracked' | 'startup';
+    };
+  };
+  units?: UnitLabels;
   model_inputs: {
     temperature: number;
     humidity: number;
@@ -62,6 +198,109 @@ export interface RiskPrediction {
     precipitation: number;
     drought_index: number;
   };
+  model?: ModelVersion;
+  clamped_inputs?: string[]; // Inputs outside the model's training range
+  timestamp: string;
+}
+
+export interface RiskGrid {
+  grid: {
+    min_lat: number;
+    min_lon: number;
+    max_lat: number;
+    max_lon: number;
+    resolution: number; // Cell size in degrees
+    rows: number;
+    cols: number;
+  };
+  cell_order: string; // Row-major from the south-west corner
+  probabilities: number[];
+  samples: {
+    source: 'observations' | 'weather_api';
+    count: number;
+  };
+  clamped_cells: number;
+  model: ModelVersion;
+  timestamp: string;
+}
+
+export interface AirQualityForecastDay {
+  date: string;
+  aqi: number | null;
+  category: string;
+  category_number: number;
+  action_day: boolean;
+  pollutants: {
+    parameter: string;
+    aqi: number | null;
+    category: string;
+    category_number: number;
+    action_day: boolean;
+  }[];
+}
+
+export interface AirQualityForecast {
+  location: string;
+  issued_on: string | null;
+  discussion: string | null;
+  days: AirQualityForecastDay[];
+  cached: boolean;
+  timestamp: string;
+}
+
+export interface RiskForecast {
+  location: {
+    latitude: number;
+    longitude: number;
+  };
+  units: UnitLabels;
+  utc_offset_seconds: number;
+  recorded_precipitation: PrecipitationCoverage;
+  drought_index: DroughtIndex;
+  model: ModelVersion;
+  daily: {
+    date: string;
+    max_probability: number;
+    level: string;
+    peak_time: string;
+  }[];
+  timeline: {
+    time: string;
+    probability: number;
+    level: string;
+    weather: WeatherData;
+    precipitation: number;
+    precipitation_probability: number;
+    drought_index: number;
+    fire_weather: FireWeather;
+    clamped_inputs: string[];
+  }[];
+  timestamp: string;
+}
+
+export interface FwiDay {
+  sensor_id: number;
+  date: string;
+  ffmc: number;
+  dmc: number;
+  dc: number;
+  isi: number;
+  bui: number;
+  fwi: number;
+  temperature: number | null; // °C at local noon
+  humidity: number | null;
+  wind_speed: number | null; // km/h
+  rainfall: number | null; // mm in the 24 hours to noon
+}
+
+export interface FireWeatherIndex {
+  location: {
+    latitude: number;
+    longitude: number;
+  };
+  sensor_id: number;
+  latest: FwiDay;
+  history: FwiDay[];
   timestamp: string;
 }
 
@@ -133,6 +372,28 @@ export const environmentalAPI = {
     }
   },
 
+  // Get one sensor with its latest and recent readings
+  getSensor: async (id: number, hours = 24): Promise<SensorDetailResponse> => {
+    try {
+      const response = await api.get(`/api/sensors/${id}?hours=${hours}`);
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch sensor:', error);
+      throw new Error('Failed to fetch sensor');
+    }
+  },
+
+  // Get sensors as a GeoJSON FeatureCollection, e.g. for L.geoJSON
+  getSensorsGeoJson: async (): Promise<SensorFeatureCollection> => {
+    try {
+      const response = await api.get('/api/sensors', { params: { format: 'geojson' } });
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch sensors as GeoJSON:', error);
+      throw new Error('Failed to fetch sensors');
+    }
+  },
+
   // Get fire risk prediction for specific coordinates
   getRiskPrediction: async (lat: number, lon: number): Promise<RiskPrediction> => {
     try {
@@ -143,4 +404,62 @@ export const environmentalAPI = {
       throw new Error('Failed to fetch risk prediction');
     }
   },
+
+  // Get the fire risk timeline for the next 5 days
+  getRiskForecast: async (lat: number, lon: number): Promise<RiskForecast> => {
+    try {
+      const response = await api.get(`/api/risk/forecast?lat=${lat}&lon=${lon}`);
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch risk forecast:', error);
+      throw new Error('Failed to fetch risk forecast');
+    }
+  },
+
+  // Get fire risk over a bounding box, one probability per grid cell
+  getRiskGrid: async (
+    bounds: { minLat: number; minLon: number; maxLat: number; maxLon: number },
+    resolution = 0.05
+  ): Promise<RiskGrid> => {
+    try {
+      const response = await api.get('/api/risk/grid', {
+        params: {
+          min_lat: bounds.minLat,
+          min_lon: bounds.minLon,
+          max_lat: bounds.maxLat,
+          max_lon: bounds.maxLon,
+          resolution,
+        },
+      });
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch risk grid:', error);
+      throw new Error('Failed to fetch risk grid');
+    }
+  },
+
+  // URL template for a map layer's vector tiles, for use as a map tile source
+  tileUrl: (layer: TileLayer): string => `${API_BASE_URL}/tiles/${layer}/{z}/{x}/{y}.mvt`,
+
+  // Get the multi-day AirNow air quality forecast for a location
+  getAirQualityForecast: async (lat: number, lon: number): Promise<AirQualityForecast> => {
+    try {
+      const response = await api.get(`/api/air-quality/forecast?lat=${lat}&lon=${lon}`);
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch air quality forecast:', error);
+      throw new Error('Failed to fetch air quality forecast');
+    }
+  },
+
+  // Get the Canadian Fire Weather Index components for the nearest sensor
+  getFireWeatherIndex: async (lat: number, lon: number, days = 7): Promise<FireWeatherIndex> => {
+    try {
+      const response = await api.get(`/api/indices/fwi?lat=${lat}&lon=${lon}&days=${days}`);
+      return response.data;
+    } catch (error) {
+      console.error('Failed to fetch fire weather index:', error);
+      throw new Error('Failed to fetch fire weather index');
+    }
+  },
 };
\ No newline at end of file
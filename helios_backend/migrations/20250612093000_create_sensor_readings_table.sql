@@ -0,0 +1,20 @@
+-- Time-series observations reported by (or fetched for) each sensor
+CREATE TABLE IF NOT EXISTS sensor_readings (
+    id BIGSERIAL PRIMARY KEY,
+    sensor_id INTEGER NOT NULL REFERENCES sensors (id) ON DELETE CASCADE,
+    parameter VARCHAR(50) NOT NULL,
+    value DOUBLE PRECISION,
+    unit VARCHAR(20),
+    aqi INTEGER,
+    observed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    source VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    -- The same observation fetched twice should not produce two rows
+    CONSTRAINT uq_sensor_readings_observation UNIQUE (sensor_id, parameter, source, observed_at)
+);
+
+-- History queries always filter by sensor and time window
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_sensor_time ON sensor_readings (sensor_id, observed_at DESC);
+
+-- Create index on parameter for filtering
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_parameter ON sensor_readings (parameter);
@@ -0,0 +1,14 @@
+-- One row per pass of the background ingestion worker
+CREATE TABLE IF NOT EXISTS ingestion_runs (
+    id BIGSERIAL PRIMARY KEY,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    sensors_total INTEGER NOT NULL,
+    sensors_succeeded INTEGER NOT NULL,
+    sensors_failed INTEGER NOT NULL,
+    readings_inserted BIGINT NOT NULL,
+    errors TEXT[] NOT NULL DEFAULT '{}'
+);
+
+-- Status queries read the most recent runs
+CREATE INDEX IF NOT EXISTS idx_ingestion_runs_started_at ON ingestion_runs (started_at DESC);
@@ -0,0 +1,11 @@
+-- Keetch-Byram Drought Index per sensor, one row per completed (UTC) day
+CREATE TABLE IF NOT EXISTS kbdi_daily (
+    sensor_id INTEGER NOT NULL REFERENCES sensors (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    kbdi DOUBLE PRECISION NOT NULL,                     -- 0-800, hundredths of an inch of moisture deficit
+    spell_rainfall DOUBLE PRECISION NOT NULL DEFAULT 0, -- inches since the current wet spell began
+    max_temperature DOUBLE PRECISION,                   -- °C, NULL when nothing was observed that day
+    rainfall DOUBLE PRECISION,                          -- mm
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (sensor_id, date)
+);
@@ -0,0 +1,17 @@
+-- Canadian Fire Weather Index components per sensor, one row per day at local noon
+CREATE TABLE IF NOT EXISTS fwi_daily (
+    sensor_id INTEGER NOT NULL REFERENCES sensors (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    ffmc DOUBLE PRECISION NOT NULL,
+    dmc DOUBLE PRECISION NOT NULL,
+    dc DOUBLE PRECISION NOT NULL,
+    isi DOUBLE PRECISION NOT NULL,
+    bui DOUBLE PRECISION NOT NULL,
+    fwi DOUBLE PRECISION NOT NULL,
+    temperature DOUBLE PRECISION, -- °C at noon; NULL when the day was carried forward unobserved
+    humidity DOUBLE PRECISION,    -- %
+    wind_speed DOUBLE PRECISION,  -- km/h
+    rainfall DOUBLE PRECISION,    -- mm in the 24 hours ending at noon
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (sensor_id, date)
+);
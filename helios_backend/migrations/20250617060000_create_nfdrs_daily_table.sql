@@ -0,0 +1,10 @@
+-- NFDRS 100- and 1000-hour dead fuel moistures per sensor, one row per completed (UTC) day
+CREATE TABLE IF NOT EXISTS nfdrs_daily (
+    sensor_id INTEGER NOT NULL REFERENCES sensors (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    hundred_hour DOUBLE PRECISION NOT NULL,           -- %
+    thousand_hour DOUBLE PRECISION NOT NULL,          -- %
+    thousand_hour_boundary DOUBLE PRECISION NOT NULL, -- %, the day's boundary condition
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (sensor_id, date)
+);
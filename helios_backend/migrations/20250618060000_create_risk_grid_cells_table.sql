@@ -0,0 +1,18 @@
+-- Risk scored by /api/risk/grid, kept so the risk map tiles can draw the latest cells
+CREATE TABLE IF NOT EXISTS risk_grid_cells (
+    id BIGSERIAL PRIMARY KEY,
+    resolution DOUBLE PRECISION NOT NULL, -- Cell size in degrees
+    south DOUBLE PRECISION NOT NULL,      -- South-west corner, rounded to 1e-6 degrees
+    west DOUBLE PRECISION NOT NULL,
+    cell GEOMETRY(Polygon, 4326) NOT NULL,
+    probability REAL NOT NULL,
+    level VARCHAR(20) NOT NULL,
+    model_version VARCHAR(50) NOT NULL,
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Scoring the same cell again replaces it
+    CONSTRAINT uq_risk_grid_cells_cell UNIQUE (resolution, south, west)
+);
+
+-- Tiles select fresh cells by location
+CREATE INDEX IF NOT EXISTS idx_risk_grid_cells_cell ON risk_grid_cells USING GIST (cell);
+CREATE INDEX IF NOT EXISTS idx_risk_grid_cells_computed_at ON risk_grid_cells (computed_at DESC);
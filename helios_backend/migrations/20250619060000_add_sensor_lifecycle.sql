@@ -0,0 +1,29 @@
+-- Sensors are decommissioned rather than deleted so their history stays queryable
+ALTER TABLE sensors ADD COLUMN IF NOT EXISTS decommissioned_at TIMESTAMP WITH TIME ZONE;
+
+-- Keep updated_at current on every change, whoever makes it
+CREATE OR REPLACE FUNCTION set_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_sensors_updated_at ON sensors;
+CREATE TRIGGER trg_sensors_updated_at
+    BEFORE UPDATE ON sensors
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Running seed_sensors twice stored every station twice; number the later copies so the
+-- names can be made unique without losing their readings
+UPDATE sensors s
+SET name = s.name || ' (' || s.id || ')'
+WHERE EXISTS (
+    SELECT 1 FROM sensors o
+    WHERE o.data_source = s.data_source AND o.name = s.name AND o.id < s.id
+);
+
+-- One active station per name within a data source; a decommissioned station's name can be reused
+CREATE UNIQUE INDEX IF NOT EXISTS uq_sensors_data_source_name
+    ON sensors (data_source, name)
+    WHERE decommissioned_at IS NULL;
@@ -0,0 +1,7 @@
+-- The upstream network's own identifier for a station (e.g. AirNow AQSID), so imports can
+-- update stations they have seen before instead of adding them again
+ALTER TABLE sensors ADD COLUMN IF NOT EXISTS external_id VARCHAR(100);
+
+-- NULLs never conflict, so stations added by hand need no external id
+ALTER TABLE sensors DROP CONSTRAINT IF EXISTS uq_sensors_external_id;
+ALTER TABLE sensors ADD CONSTRAINT uq_sensors_external_id UNIQUE (data_source, external_id);
@@ -0,0 +1,6 @@
+-- Station metadata filled in by importers such as seed_sensors
+ALTER TABLE sensors
+    ADD COLUMN IF NOT EXISTS elevation DOUBLE PRECISION,              -- Meters above sea level
+    ADD COLUMN IF NOT EXISTS agency VARCHAR(255),                     -- Operator, e.g. "Oregon DEQ"
+    ADD COLUMN IF NOT EXISTS parameters TEXT[] NOT NULL DEFAULT '{}', -- What it measures, e.g. {PM2.5,O3}
+    ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;    -- Reporting upstream; inactive stations are not polled
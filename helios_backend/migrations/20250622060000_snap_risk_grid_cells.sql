@@ -0,0 +1,3 @@
+-- Risk cells are now scored by a background job on a global lattice. Cells stored by
+-- /api/risk/grid were keyed on each request's box and never replaced; the job repopulates.
+DELETE FROM risk_grid_cells;
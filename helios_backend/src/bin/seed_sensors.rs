@@ -1,176 +1,611 @@
 use std::error::Error;
-use serde::Deserialize;
-use dotenvy::dotenv;
-
-mod models {
-    use serde::{Deserialize, Serialize};
-    
-    #[derive(Debug, Deserialize)]
-    pub struct NewSensor {
+use std::path::PathBuf;
+use sqlx::Connection;
+
+const USAGE: &str = "\
+Usage: seed_sensors [OPTIONS] FILE
+
+Import monitoring stations from FILE, updating stations imported before.
+
+Options:
+  --format airnow|csv|geojson   File format; inferred from the extension when omitted
+                                (.dat: airnow, .geojson/.json: geojson, .csv: airnow if the
+                                header has an AQSID column, otherwise csv)
+  --data-source NAME            Data source for csv and geojson stations (airnow: AirNow)
+  --columns FIELD=COLUMN,...    Column or property names for csv and geojson, for any of
+                                id, name, latitude, longitude, data_source and the optional
+                                elevation (m), agency, parameters (separated by ;) and active
+  --dry-run                     Report what would change without writing anything";
+
+mod sources {
+    use helios_backend::models::NewSensor;
+    use serde_json::Value;
+    use std::collections::{HashMap, HashSet};
+
+    /// Station files seed_sensors understands
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Format {
+        AirNow,  // AirNow monitoring site list, pipe- or comma-delimited
+        Csv,     // Any CSV with a header row, columns named by ColumnMap
+        GeoJson, // A FeatureCollection of points
+    }
+
+    impl std::str::FromStr for Format {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "airnow" => Ok(Format::AirNow),
+                "csv" => Ok(Format::Csv),
+                "geojson" => Ok(Format::GeoJson),
+                other => Err(format!("Unknown format '{}', expected airnow, csv or geojson", other)),
+            }
+        }
+    }
+
+    impl Format {
+        pub fn infer(path: &std::path::Path, contents: &str) -> Option<Format> {
+            let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+            match extension.as_str() {
+                "dat" => Some(Format::AirNow),
+                "geojson" | "json" => Some(Format::GeoJson),
+                "csv" => {
+                    let header = contents.lines().next().unwrap_or_default().to_ascii_lowercase();
+                    Some(if header.contains("aqsid") { Format::AirNow } else { Format::Csv })
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Where each field is found in a csv row or GeoJSON feature's properties
+    #[derive(Debug, Clone)]
+    pub struct ColumnMap {
+        pub id: String,
+        pub name: String,
+        pub latitude: String,
+        pub longitude: String,
+        pub data_source: String,
+        pub elevation: String,
+        pub agency: String,
+        pub parameters: String,
+        pub active: String,
+    }
+
+    impl Default for ColumnMap {
+        fn default() -> Self {
+            ColumnMap {
+                id: "id".to_string(),
+                name: "name".to_string(),
+                latitude: "latitude".to_string(),
+                longitude: "longitude".to_string(),
+                data_source: "data_source".to_string(),
+                elevation: "elevation".to_string(),
+                agency: "agency".to_string(),
+                parameters: "parameters".to_string(),
+                active: "active".to_string(),
+            }
+        }
+    }
+
+    impl ColumnMap {
+        /// Override fields from `id=site_id,latitude=lat`
+        pub fn parse(spec: &str) -> Result<Self, String> {
+            let mut columns = ColumnMap::default();
+            for pair in spec.split(',').filter(|pair| !pair.trim().is_empty()) {
+                let (field, column) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid column mapping '{}', expected FIELD=COLUMN", pair))?;
+                let column = column.trim().to_string();
+                match field.trim() {
+                    "id" => columns.id = column,
+                    "name" => columns.name = column,
+                    "latitude" => columns.latitude = column,
+                    "longitude" => columns.longitude = column,
+                    "data_source" => columns.data_source = column,
+                    "elevation" => columns.elevation = column,
+                    "agency" => columns.agency = column,
+                    "parameters" => columns.parameters = column,
+                    "active" => columns.active = column,
+                    other => {
+                        return Err(format!(
+                            "Unknown field '{}', expected id, name, latitude, longitude, data_source, \
+                             elevation, agency, parameters or active",
+                            other
+                        ))
+                    }
+                }
+            }
+            Ok(columns)
+        }
+    }
+
+    /// One station as read from a file
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Site {
+        pub external_id: String,
         pub name: String,
         pub data_source: String,
         pub latitude: f64,
         pub longitude: f64,
+        pub elevation: Option<f64>, // Meters
+        pub agency: Option<String>,
+        pub parameters: Vec<String>,
+        pub active: bool,
     }
-}
 
-mod database {
-    use sqlx::{Pool, Postgres, Error as SqlxError};
-    use crate::models::NewSensor;
-    
-    pub struct Database {
-        pool: Pool<Postgres>,
-    }
-    
-    impl Database {
-        pub fn new(pool: Pool<Postgres>) -> Self {
-            Self { pool }
-        }
-        
-        pub async fn insert_sensor(&self, sensor: NewSensor) -> Result<i32, SqlxError> {
-            let record = sqlx::query!(
-                r#"
-                INSERT INTO sensors (name, data_source, location)
-                VALUES ($1, $2, ST_SetSRID(ST_MakePoint($3, $4), 4326))
-                RETURNING id
-                "#,
-                sensor.name,
-                sensor.data_source,
-                sensor.longitude,
-                sensor.latitude
-            )
-            .fetch_one(&self.pool)
-            .await?;
-
-            Ok(record.id)
-        }
-        
-        pub async fn get_sensor_count(&self) -> Result<i64, SqlxError> {
-            let record = sqlx::query!("SELECT COUNT(*) as count FROM sensors")
-                .fetch_one(&self.pool)
-                .await?;
-            
-            Ok(record.count.unwrap_or(0))
+    /// Stations read from a file, and why any entries were left out
+    #[derive(Debug, Default)]
+    pub struct Parsed {
+        pub sites: Vec<Site>,
+        pub skipped: Vec<String>,
+    }
+
+    impl Parsed {
+        /// Whether the site was usable
+        fn push(&mut self, label: String, site: Result<Site, String>) -> bool {
+            match site.and_then(validate) {
+                Ok(site) => {
+                    self.sites.push(site);
+                    true
+                }
+                Err(reason) => {
+                    self.skipped.push(format!("{}: {}", label, reason));
+                    false
+                }
+            }
+        }
+
+        /// The first entry for a station wins; files sometimes repeat one
+        fn dedupe(mut self) -> Self {
+            let mut seen = HashSet::new();
+            let mut sites = Vec::with_capacity(self.sites.len());
+            for site in self.sites {
+                if seen.insert((site.data_source.clone(), site.external_id.clone())) {
+                    sites.push(site);
+                } else {
+                    self.skipped.push(format!("{}: listed more than once", site.external_id));
+                }
+            }
+            self.sites = sites;
+            self
+        }
+    }
+
+    /// The sensor a site is stored as
+    pub fn new_sensor(site: &Site) -> NewSensor {
+        NewSensor {
+            name: site.name.clone(),
+            data_source: site.data_source.clone(),
+            latitude: site.latitude,
+            longitude: site.longitude,
+            external_id: Some(site.external_id.clone()),
+            elevation: site.elevation,
+            agency: site.agency.clone(),
+            parameters: site.parameters.clone(),
+            active: site.active,
+        }
+    }
+
+    /// The same checks the sensor API applies, plus what only a file gets wrong
+    fn validate(site: Site) -> Result<Site, String> {
+        if site.data_source.is_empty() {
+            return Err("no data source; pass --data-source".to_string());
+        }
+        if site.latitude == 0.0 && site.longitude == 0.0 {
+            return Err("no coordinates".to_string()); // Placeholder used by some site lists
+        }
+        new_sensor(&site).validate()?;
+        Ok(site)
+    }
+
+    fn number(value: Option<&str>, field: &str) -> Result<f64, String> {
+        let value = value.map(str::trim).filter(|v| !v.is_empty()).ok_or_else(|| format!("no {}", field))?;
+        value.parse::<f64>().map_err(|_| format!("invalid {} '{}'", field, value))
+    }
+
+    /// An optional field: missing or empty is None, anything else must parse
+    fn optional_number(value: Option<&str>, field: &str) -> Result<Option<f64>, String> {
+        match value.map(str::trim).filter(|v| !v.is_empty()) {
+            Some(value) => number(Some(value), field).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn optional_text(value: Option<&str>) -> Option<String> {
+        value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+    }
+
+    /// Missing means active
+    fn active_flag(value: Option<&str>) -> Result<bool, String> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("true") | Some("1") | Some("yes") | Some("active") => Ok(true),
+            Some("false") | Some("0") | Some("no") | Some("inactive") => Ok(false),
+            Some(other) => Err(format!("invalid active flag '{}'", other)),
+        }
+    }
+
+    fn parameter_list(value: Option<&str>) -> Vec<String> {
+        value
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    // Column order of AirNow's Monitoring_Site_Locations_V2.dat, which has no header row
+    const AIRNOW_COLUMNS: [&str; 22] = [
+        "StationID", "AQSID", "FullAQSID", "Parameter", "MonitorType", "SiteCode", "SiteName", "Status",
+        "AgencyID", "AgencyName", "EPARegion", "Latitude", "Longitude", "Elevation", "GMTOffset",
+        "CountryFIPS", "CBSA_ID", "CBSA_Name", "StateAQSCode", "StateAbbreviation", "CountyAQSCode",
+        "CountyName",
+    ];
+
+    /// AirNow lists each site once per parameter it measures; they become one station keyed by AQSID
+    pub fn parse_airnow(contents: &str) -> Result<Parsed, String> {
+        let first_line = contents.lines().next().unwrap_or_default();
+        let delimiter = if first_line.contains('|') { b'|' } else { b',' };
+        let has_header = first_line.to_ascii_lowercase().contains("aqsid");
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(has_header)
+            .flexible(true)
+            .from_reader(contents.as_bytes());
+        let header: Vec<String> = if has_header {
+            reader.headers().map_err(|e| e.to_string())?.iter().map(|h| h.trim().to_string()).collect()
+        } else {
+            AIRNOW_COLUMNS.iter().map(|c| c.to_string()).collect()
+        };
+        let index = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| header.iter().position(|h| h.eq_ignore_ascii_case(name)))
+        };
+        let id_column = index(&["AQSID", "FullAQSID"]).ok_or("No AQSID column")?;
+        let name_column = index(&["SiteName", "Site Name"]).ok_or("No SiteName column")?;
+        let latitude_column = index(&["Latitude"]).ok_or("No Latitude column")?;
+        let longitude_column = index(&["Longitude"]).ok_or("No Longitude column")?;
+        let state_column = index(&["StateAbbreviation", "State"]);
+        let parameter_column = index(&["Parameter"]);
+        let status_column = index(&["Status"]);
+        let agency_column = index(&["AgencyName"]);
+        let elevation_column = index(&["Elevation"]);
+
+        let mut parsed = Parsed::default();
+        let mut seen: HashMap<String, usize> = HashMap::new(); // AQSID to its index in parsed.sites
+        for (line, record) in reader.records().enumerate() {
+            let label = format!("line {}", line + 1 + has_header as usize);
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    parsed.skipped.push(format!("{}: {}", label, e));
+                    continue;
+                }
+            };
+            let field = |column: usize| record.get(column).map(str::trim).unwrap_or_default();
+
+            let external_id = field(id_column).to_string();
+            let parameter = parameter_column.map(field).filter(|p| !p.is_empty());
+            let active = status_column.is_none_or(|column| field(column).eq_ignore_ascii_case("active"));
+            if let Some(&i) = seen.get(&external_id) {
+                // Another monitor at a site already read; the site is active if any monitor is
+                let site = &mut parsed.sites[i];
+                if let Some(parameter) = parameter {
+                    if !site.parameters.iter().any(|p| p == parameter) {
+                        site.parameters.push(parameter.to_string());
+                    }
+                }
+                site.active |= active;
+                continue;
+            }
+            let site = (|| -> Result<Site, String> {
+                let name = match state_column.map(field).filter(|state| !state.is_empty()) {
+                    Some(state) => format!("{}, {}", field(name_column), state),
+                    None => field(name_column).to_string(),
+                };
+                Ok(Site {
+                    external_id: external_id.clone(),
+                    name,
+                    data_source: "AirNow".to_string(),
+                    latitude: number(record.get(latitude_column), "latitude")?,
+                    longitude: number(record.get(longitude_column), "longitude")?,
+                    elevation: optional_number(elevation_column.and_then(|column| record.get(column)), "elevation")?,
+                    agency: optional_text(agency_column.and_then(|column| record.get(column))),
+                    parameters: parameter.map(str::to_string).into_iter().collect(),
+                    active,
+                })
+            })();
+            if parsed.push(label, site) {
+                seen.insert(external_id, parsed.sites.len() - 1);
+            }
+        }
+        Ok(parsed.dedupe())
+    }
+
+    pub fn parse_csv(contents: &str, columns: &ColumnMap, data_source: Option<&str>) -> Result<Parsed, String> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(contents.as_bytes());
+        let header: HashMap<String, usize> = reader
+            .headers()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.trim().to_string(), i))
+            .collect();
+        for column in [&columns.id, &columns.name, &columns.latitude, &columns.longitude] {
+            if !header.contains_key(column) {
+                return Err(format!("No '{}' column; map it with --columns", column));
+            }
+        }
+        let source_column = header.get(&columns.data_source).copied();
+        if data_source.is_none() && source_column.is_none() {
+            return Err(format!("No '{}' column; pass --data-source", columns.data_source));
+        }
+
+        let mut parsed = Parsed::default();
+        for (line, record) in reader.records().enumerate() {
+            let label = format!("line {}", line + 2);
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    parsed.skipped.push(format!("{}: {}", label, e));
+                    continue;
+                }
+            };
+            let field = |column: &String| record.get(header[column]).map(str::trim);
+            let optional = |column: &String| header.get(column).and_then(|&i| record.get(i));
+
+            let site = (|| -> Result<Site, String> {
+                Ok(Site {
+                    external_id: field(&columns.id).unwrap_or_default().to_string(),
+                    name: field(&columns.name).unwrap_or_default().to_string(),
+                    data_source: data_source
+                        .or_else(|| source_column.and_then(|column| record.get(column)).map(str::trim))
+                        .unwrap_or_default()
+                        .to_string(),
+                    latitude: number(field(&columns.latitude), "latitude")?,
+                    longitude: number(field(&columns.longitude), "longitude")?,
+                    elevation: optional_number(optional(&columns.elevation), "elevation")?,
+                    agency: optional_text(optional(&columns.agency)),
+                    parameters: parameter_list(optional(&columns.parameters)),
+                    active: active_flag(optional(&columns.active))?,
+                })
+            })();
+            parsed.push(label, site);
         }
+        Ok(parsed.dedupe())
     }
+
+    /// Point features; the id is the feature's `id` or the mapped property
+    pub fn parse_geojson(contents: &str, columns: &ColumnMap, data_source: Option<&str>) -> Result<Parsed, String> {
+        let collection: Value = serde_json::from_str(contents).map_err(|e| format!("Invalid GeoJSON: {}", e))?;
+        let features = match collection["type"].as_str() {
+            Some("FeatureCollection") => collection["features"].as_array().cloned().unwrap_or_default(),
+            Some("Feature") => vec![collection.clone()],
+            _ => return Err("Expected a GeoJSON FeatureCollection".to_string()),
+        };
+
+        let text = |value: &Value| match value {
+            Value::String(s) => Some(s.trim().to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+
+        let mut parsed = Parsed::default();
+        for (i, feature) in features.iter().enumerate() {
+            let properties = &feature["properties"];
+            let site = (|| -> Result<Site, String> {
+                if feature["geometry"]["type"] != "Point" {
+                    return Err("not a point".to_string());
+                }
+                let coordinates = &feature["geometry"]["coordinates"];
+                Ok(Site {
+                    external_id: text(&feature["id"]).or_else(|| text(&properties[&columns.id])).unwrap_or_default(),
+                    name: text(&properties[&columns.name]).unwrap_or_default(),
+                    data_source: data_source
+                        .map(str::to_string)
+                        .or_else(|| text(&properties[&columns.data_source]))
+                        .unwrap_or_default(),
+                    // Positions are longitude first; a third is the elevation
+                    latitude: coordinates[1].as_f64().ok_or("no latitude")?,
+                    longitude: coordinates[0].as_f64().ok_or("no longitude")?,
+                    elevation: properties[&columns.elevation].as_f64().or_else(|| coordinates[2].as_f64()),
+                    agency: text(&properties[&columns.agency]),
+                    parameters: match &properties[&columns.parameters] {
+                        Value::Array(parameters) => parameters.iter().filter_map(text).collect(),
+                        other => parameter_list(text(other).as_deref()),
+                    },
+                    active: match &properties[&columns.active] {
+                        Value::Bool(active) => *active,
+                        other => active_flag(text(other).as_deref())?,
+                    },
+                })
+            })();
+            parsed.push(format!("feature {}", i), site);
+        }
+        Ok(parsed.dedupe())
+    }
+}
+
+use helios_backend::database::Database;
+use helios_backend::models::UpsertOutcome;
+use sources::{new_sensor, ColumnMap, Format};
+
+struct Args {
+    path: PathBuf,
+    format: Option<Format>,
+    data_source: Option<String>,
+    columns: ColumnMap,
+    dry_run: bool,
 }
 
-use models::NewSensor;
-use database::Database;
-
-#[derive(Debug, Deserialize)]
-struct AirNowSensor {
-    #[serde(rename = "Site Name")]
-    site_name: String,
-    #[serde(rename = "Latitude")]
-    latitude: f64,
-    #[serde(rename = "Longitude")]
-    longitude: f64,
-    #[serde(rename = "State")]
-    state: Option<String>,
-    #[serde(rename = "City")]
-    city: Option<String>,
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut path = None;
+    let mut format = None;
+    let mut data_source = None;
+    let mut columns = ColumnMap::default();
+    let mut dry_run = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--format" => format = Some(value("--format")?.parse()?),
+            "--data-source" => data_source = Some(value("--data-source")?),
+            "--columns" => columns = ColumnMap::parse(&value("--columns")?)?,
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            file if path.is_none() => path = Some(PathBuf::from(file)),
+            extra => return Err(format!("Unexpected argument {}", extra)),
+        }
+    }
+
+    Ok(Args {
+        path: path.ok_or("No file given")?,
+        format,
+        data_source,
+        columns,
+        dry_run,
+    })
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenvy::dotenv().ok();
-    
+
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let contents = std::fs::read_to_string(&args.path)
+        .map_err(|e| format!("Failed to read {}: {}", args.path.display(), e))?;
+    let format = args
+        .format
+        .or_else(|| Format::infer(&args.path, &contents))
+        .ok_or("Cannot tell the file format from its extension; pass --format")?;
+    let parsed = match format {
+        Format::AirNow => sources::parse_airnow(&contents),
+        Format::Csv => sources::parse_csv(&contents, &args.columns, args.data_source.as_deref()),
+        Format::GeoJson => sources::parse_geojson(&contents, &args.columns, args.data_source.as_deref()),
+    }
+    .map_err(|e| format!("Failed to read {}: {}", args.path.display(), e))?;
+
+    for reason in &parsed.skipped {
+        eprintln!("- Skipped {}", reason);
+    }
+    println!("Importing {} stations from {} ({:?})...", parsed.sites.len(), args.path.display(), format);
+
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set in .env file");
-    
+
     let pool = sqlx::postgres::PgPoolOptions::new()
         .max_connections(5)
         .connect(&database_url)
         .await?;
-    
-    let db = Database::new(pool);
-    
-    // Sample AirNow sensor data for Oregon region
-    // In a real implementation, you would download this from:
-    // https://www.airnow.gov/index.cfm?action=airnow.global_summary
-    let sample_sensors = vec![
-        AirNowSensor {
-            site_name: "Portland-SE Lafayette".to_string(),
-            latitude: 45.4871,
-            longitude: -122.6037,
-            state: Some("Oregon".to_string()),
-            city: Some("Portland".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Eugene-Amazon Park".to_string(),
-            latitude: 44.0462,
-            longitude: -123.0351,
-            state: Some("Oregon".to_string()),
-            city: Some("Eugene".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Medford-Welch Street".to_string(),
-            latitude: 42.3265,
-            longitude: -122.8756,
-            state: Some("Oregon".to_string()),
-            city: Some("Medford".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Bend-NE 27th Street".to_string(),
-            latitude: 44.0582,
-            longitude: -121.2767,
-            state: Some("Oregon".to_string()),
-            city: Some("Bend".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Salem-Lancaster Drive".to_string(),
-            latitude: 44.9429,
-            longitude: -123.0351,
-            state: Some("Oregon".to_string()),
-            city: Some("Salem".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Klamath Falls-Lakeview".to_string(),
-            latitude: 42.2249,
-            longitude: -121.7817,
-            state: Some("Oregon".to_string()),
-            city: Some("Klamath Falls".to_string()),
-        },
-        AirNowSensor {
-            site_name: "Corvallis-Circle Boulevard".to_string(),
-            latitude: 44.5646,
-            longitude: -123.2620,
-            state: Some("Oregon".to_string()),
-            city: Some("Corvallis".to_string()),
-        },
-    ];
-    
-    println!("Seeding {} sensors into database...", sample_sensors.len());
-    
-    for sensor_data in sample_sensors {
-        let full_name = if let (Some(city), Some(state)) = (&sensor_data.city, &sensor_data.state) {
-            format!("{} - {}, {}", sensor_data.site_name, city, state)
-        } else {
-            sensor_data.site_name.clone()
-        };
-        
-        let new_sensor = NewSensor {
-            name: full_name,
-            data_source: "AirNow".to_string(),
-            latitude: sensor_data.latitude,
-            longitude: sensor_data.longitude,
-        };
-        
-        match db.insert_sensor(new_sensor).await {
-            Ok(id) => println!("✓ Inserted sensor '{}' with ID {}", sensor_data.site_name, id),
-            Err(e) => eprintln!("✗ Failed to insert sensor '{}': {}", sensor_data.site_name, e),
+
+    // One transaction for the whole file, so a dry run can roll everything back; each station
+    // gets a savepoint so one failure doesn't abort the rest
+    let mut tx = pool.begin().await?;
+    let (mut inserted, mut updated, mut unchanged, mut skipped) = (0, 0, 0, parsed.skipped.len());
+    for site in &parsed.sites {
+        let mut savepoint = tx.begin().await?;
+        match Database::upsert_sensor_on(&mut savepoint, &new_sensor(site)).await {
+            Ok((_, outcome)) => {
+                savepoint.commit().await?;
+                match outcome {
+                    UpsertOutcome::Inserted => inserted += 1,
+                    UpsertOutcome::Updated => updated += 1,
+                    UpsertOutcome::Unchanged => unchanged += 1,
+                    UpsertOutcome::Decommissioned => {
+                        eprintln!("- Skipped '{}' ({}): decommissioned", site.name, site.external_id);
+                        skipped += 1;
+                    }
+                }
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                eprintln!("✗ Failed to import '{}' ({}): {}", site.name, site.external_id, e);
+                skipped += 1;
+            }
         }
     }
-    
-    println!("Sensor seeding completed!");
-      // Verify the data was inserted
-    match db.get_sensor_count().await {
+
+    if args.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    println!(
+        "{}: {} inserted, {} updated, {} unchanged, {} skipped",
+        if args.dry_run { "Dry run, nothing written" } else { "Sensor import completed" },
+        inserted,
+        updated,
+        unchanged,
+        skipped
+    );
+    // Verify the data was inserted
+    match Database::new(pool).get_sensor_count().await {
         Ok(count) => {
             println!("\nTotal sensors in database: {}", count);
         }
         Err(e) => eprintln!("Failed to verify sensors: {}", e),
     }
-    
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::sources::*;
+
+    #[test]
+    fn test_sites_are_read_from_each_format() {
+        // Two parameters at one AirNow site become one station
+        let airnow = "\
+000410510080|410510080|840410510080|O3|Permanent|0080|Portland - SE Lafayette|Active|OR1|Oregon DEQ|R10|45.4965|-122.6034|45.0|-8.00|US|38900|Portland-Vancouver-Hillsboro, OR-WA|41|OR|051|MULTNOMAH
+000410510080|410510080|840410510080|PM2.5|Permanent|0080|Portland - SE Lafayette|Active|OR1|Oregon DEQ|R10|45.4965|-122.6034|45.0|-8.00|US|38900|Portland-Vancouver-Hillsboro, OR-WA|41|OR|051|MULTNOMAH
+000410170120|410170120|840410170120|PM2.5|Permanent|0120|Bend Pump Station|Active|OR1|Oregon DEQ|R10||-121.3087|1100.0|-8.00|US|13460|Bend, OR|41|OR|017|DESCHUTES";
+        let parsed = parse_airnow(airnow).unwrap();
+        assert_eq!(parsed.sites.len(), 1);
+        assert_eq!(parsed.sites[0].external_id, "410510080");
+        assert_eq!(parsed.sites[0].name, "Portland - SE Lafayette, OR");
+        assert_eq!(parsed.sites[0].parameters, vec!["O3", "PM2.5"]);
+        assert_eq!(parsed.sites[0].agency.as_deref(), Some("Oregon DEQ"));
+        assert_eq!(parsed.sites[0].elevation, Some(45.0));
+        assert_eq!(parsed.skipped, vec!["line 3: no latitude"]);
+
+        let columns = ColumnMap::parse("id=sensor_index,latitude=lat,longitude=lon").unwrap();
+        let csv = "sensor_index,name,lat,lon,parameters,active\n131075,Sisters Library,44.2909,-121.5493,PM2.5;humidity,no\n,No Id,44.0,-121.0,,\n";
+        let parsed = parse_csv(csv, &columns, Some("PurpleAir")).unwrap();
+        assert_eq!(parsed.sites.len(), 1);
+        assert_eq!((parsed.sites[0].latitude, parsed.sites[0].longitude), (44.2909, -121.5493));
+        assert_eq!(parsed.sites[0].parameters, vec!["PM2.5", "humidity"]);
+        assert!(!parsed.sites[0].active);
+        assert_eq!(parsed.skipped.len(), 1);
+        assert!(parse_csv(csv, &ColumnMap::default(), Some("PurpleAir")).is_err());
+
+        // Sites get the same validation as sensors posted to the API
+        let long_id = format!("sensor_index,name,lat,lon\n{},Sisters Library,44.2909,-121.5493\n", "9".repeat(101));
+        let parsed = parse_csv(&long_id, &columns, Some("PurpleAir")).unwrap();
+        assert!(parsed.sites.is_empty());
+        assert!(parsed.skipped[0].contains("external_id"), "{:?}", parsed.skipped);
+
+        let geojson = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": 131075, "geometry": {"type": "Point", "coordinates": [-121.5493, 44.2909]},
+             "properties": {"name": "Sisters Library"}}
+        ]}"#;
+        let parsed = parse_geojson(geojson, &ColumnMap::default(), Some("PurpleAir")).unwrap();
+        assert_eq!(parsed.sites[0].external_id, "131075");
+        assert_eq!(parsed.sites[0].latitude, 44.2909);
+    }
+}
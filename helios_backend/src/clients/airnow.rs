@@ -1,8 +1,17 @@
 // src/clients/airnow.rs
 
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
 use reqwest::Client;
-use serde::Deserialize;
-use std::env;
+use serde::{Deserialize, Serialize};
+
+use super::fixtures::{fixture_coords, FixtureStore, AIRNOW_FIXTURES};
+use super::{AirQualityProvider, ProviderError};
+
+pub const AIRNOW_BASE_URL: &str = "https://www.airnowapi.org";
+pub const DEFAULT_DISTANCE_MILES: u32 = 25;
+const PROVIDER: &str = "AirNow";
 
 #[derive(Deserialize, Debug)]
 pub struct AirNowResponse {
@@ -11,19 +20,14 @@ pub struct AirNowResponse {
     #[serde(rename = "Category")]
     pub category: CategoryInfo,
     #[serde(rename = "DateObserved")]
-    #[allow(dead_code)]
     pub date_observed: String,
     #[serde(rename = "HourObserved")]
-    #[allow(dead_code)]
     pub hour_observed: i32,
     #[serde(rename = "LocalTimeZone")]
-    #[allow(dead_code)]
     pub local_time_zone: String,
     #[serde(rename = "ReportingArea")]
-    #[allow(dead_code)]
     pub reporting_area: String,
     #[serde(rename = "StateCode")]
-    #[allow(dead_code)]
     pub state_code: String,
     #[serde(rename = "Latitude")]
     #[allow(dead_code)]
@@ -31,55 +35,452 @@ pub struct AirNowResponse {
     #[serde(rename = "Longitude")]
     #[allow(dead_code)]
     pub longitude: f64,
+    #[serde(rename = "ParameterName")]
+    pub parameter_name: String,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct CategoryInfo {
     #[serde(rename = "Number")]
-    #[allow(dead_code)]
     pub number: i32,
     #[serde(rename = "Name")]
     pub name: String,
 }
 
-// Simple struct for the main.rs handler
-#[derive(Debug)]
-pub struct AirQualityIndex {
+impl AirNowResponse {
+    /// AirNow reports observations as a local date, hour and time zone abbreviation
+    pub fn observed_at(&self) -> Option<DateTime<Utc>> {
+        let date = NaiveDate::parse_from_str(self.date_observed.trim(), "%Y-%m-%d").ok()?;
+        let local = date.and_hms_opt(self.hour_observed.try_into().ok()?, 0, 0)?;
+        let offset_hours = match self.local_time_zone.trim() {
+            "UTC" | "GMT" => 0,
+            "EDT" => -4,
+            "EST" | "CDT" => -5,
+            "CST" | "MDT" => -6,
+            "MST" | "PDT" => -7,
+            "PST" | "AKDT" => -8,
+            "AKST" => -9,
+            "HST" => -10,
+            _ => return None,
+        };
+        Some(local.and_utc() - Duration::hours(offset_hours))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AirNowForecastResponse {
+    #[serde(rename = "DateIssue")]
+    pub date_issue: String,
+    #[serde(rename = "DateForecast")]
+    pub date_forecast: String,
+    #[serde(rename = "ReportingArea")]
+    pub reporting_area: String,
+    #[serde(rename = "StateCode")]
+    pub state_code: String,
+    #[serde(rename = "ParameterName")]
+    pub parameter_name: String,
+    #[serde(rename = "AQI")]
+    pub aqi: i32, // -1 when only a category is forecast
+    #[serde(rename = "Category")]
+    pub category: CategoryInfo,
+    #[serde(rename = "ActionDay")]
+    pub action_day: bool,
+    #[serde(rename = "Discussion", default)]
+    pub discussion: String,
+}
+
+/// One pollutant's AQI as reported by AirNow
+#[derive(Debug, Clone, Serialize)]
+pub struct AirQualityObservation {
+    pub parameter: String, // "O3", "PM2.5", "PM10", ...
     pub aqi: i32,
     pub category: String,
+    pub category_number: i32,
+    pub observed_at: Option<DateTime<Utc>>,
 }
 
-impl From<AirNowResponse> for AirQualityIndex {
+impl From<AirNowResponse> for AirQualityObservation {
     fn from(response: AirNowResponse) -> Self {
-        AirQualityIndex {
+        AirQualityObservation {
+            parameter: response.parameter_name.trim().to_string(),
             aqi: response.aqi,
+            observed_at: response.observed_at(),
             category: response.category.name,
+            category_number: response.category.number,
+        }
+    }
+}
+
+// Simple struct for the main.rs handler
+#[derive(Debug, Clone)]
+pub struct AirQualityIndex {
+    pub aqi: i32,         // Overall AQI, taken from the dominant pollutant
+    pub category: String,
+    pub observed_at: Option<DateTime<Utc>>,
+    pub reporting_area: Option<String>, // AirNow reporting area, or the sensor a stored reading came from
+    pub state_code: Option<String>,
+    pub dominant_pollutant: Option<String>,
+    pub pollutants: Vec<AirQualityObservation>,
+}
+
+impl AirQualityIndex {
+    /// Summarise per-pollutant observations; the overall AQI is the highest one.
+    /// Returns `None` when there are no observations.
+    pub fn from_observations(
+        pollutants: Vec<AirQualityObservation>,
+        reporting_area: Option<String>,
+        state_code: Option<String>,
+    ) -> Option<Self> {
+        let dominant = pollutants.iter().max_by_key(|p| p.aqi)?.clone();
+
+        Some(AirQualityIndex {
+            aqi: dominant.aqi,
+            category: dominant.category,
+            observed_at: dominant.observed_at,
+            reporting_area,
+            state_code,
+            dominant_pollutant: Some(dominant.parameter),
+            pollutants,
+        })
+    }
+
+    /// Human readable place name, e.g. "Klamath Falls, OR"
+    pub fn location(&self) -> Option<String> {
+        let area = self.reporting_area.as_deref()?.trim();
+        match self.state_code.as_deref().map(str::trim) {
+            Some(state) if !state.is_empty() => Some(format!("{}, {}", area, state)),
+            _ => Some(area.to_string()),
         }
     }
 }
 
-pub async fn get_aqi_by_zip(zip_code: &str) -> Result<AirQualityIndex, String> {
-    let api_key = env::var("AIRNOW_API_KEY").map_err(|_| "AIRNOW_API_KEY not set".to_string())?;
-    let url = format!(
-        "https://www.airnowapi.org/aq/observation/zipCode/current/?format=application/json&zipCode={}&distance=25&API_KEY={}", 
-        zip_code, api_key
-    );
-    
-    let client = Client::new();
-    let response = client.get(&url).send().await
-        .map_err(|e| format!("API request failed: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("API request failed with status: {}", response.status()));
-    }
-    
-    let air_data: Vec<AirNowResponse> = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-    
-    // Find the PM2.5 or Ozone reading (most common AQI measurements)
-    air_data
+/// Forecast for one pollutant on one day
+#[derive(Debug, Clone, Serialize)]
+pub struct PollutantForecast {
+    pub parameter: String,
+    pub aqi: Option<i32>,
+    pub category: String,
+    pub category_number: i32,
+    pub action_day: bool,
+}
+
+/// All pollutants forecast for one day; the day takes the worst category
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastDay {
+    pub date: NaiveDate,
+    pub aqi: Option<i32>,
+    pub category: String,
+    pub category_number: i32,
+    pub action_day: bool,
+    pub pollutants: Vec<PollutantForecast>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AirQualityForecast {
+    pub reporting_area: String,
+    pub state_code: String,
+    pub issued_on: Option<NaiveDate>,
+    pub discussion: Option<String>,
+    pub days: Vec<ForecastDay>,
+}
+
+impl AirQualityForecast {
+    pub fn location(&self) -> String {
+        format!("{}, {}", self.reporting_area, self.state_code)
+    }
+}
+
+/// EPA category number (1 = Good ... 6 = Hazardous) for an AQI value
+pub fn aqi_category_number(aqi: i32) -> i32 {
+    match aqi {
+        i32::MIN..=50 => 1,
+        51..=100 => 2,
+        101..=150 => 3,
+        151..=200 => 4,
+        201..=300 => 5,
+        _ => 6,
+    }
+}
+
+/// EPA category name for an AQI value, used when only the number was stored
+pub fn aqi_category_name(aqi: i32) -> &'static str {
+    match aqi {
+        i32::MIN..=50 => "Good",
+        51..=100 => "Moderate",
+        101..=150 => "Unhealthy for Sensitive Groups",
+        151..=200 => "Unhealthy",
+        201..=300 => "Very Unhealthy",
+        _ => "Hazardous",
+    }
+}
+
+/// Parse a current-observation response body, keeping every reported pollutant
+pub fn parse_current_observations(body: &str, not_found: &str) -> Result<AirQualityIndex, ProviderError> {
+    let air_data: Vec<AirNowResponse> = serde_json::from_str(body)
+        .map_err(|e| ProviderError::Parse { provider: PROVIDER, message: e.to_string() })?;
+
+    let reporting_area = air_data.first().map(|r| r.reporting_area.trim().to_string());
+    let state_code = air_data.first().map(|r| r.state_code.trim().to_string());
+
+    // AirNow reports -1 for parameters the monitor did not measure
+    let pollutants = air_data
+        .into_iter()
+        .filter(|r| r.aqi >= 0)
+        .map(AirQualityObservation::from)
+        .collect();
+
+    AirQualityIndex::from_observations(pollutants, reporting_area, state_code)
+        .ok_or_else(|| ProviderError::NoData(not_found.to_string()))
+}
+
+fn parse_airnow_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+/// Parse a forecast response body into one entry per forecast day, oldest first
+pub fn parse_forecast(body: &str, not_found: &str) -> Result<AirQualityForecast, ProviderError> {
+    let forecast_data: Vec<AirNowForecastResponse> = serde_json::from_str(body)
+        .map_err(|e| ProviderError::Parse { provider: PROVIDER, message: e.to_string() })?;
+
+    let first = forecast_data.first()
+        .ok_or_else(|| ProviderError::NoData(not_found.to_string()))?;
+    let reporting_area = first.reporting_area.trim().to_string();
+    let state_code = first.state_code.trim().to_string();
+    let issued_on = parse_airnow_date(&first.date_issue);
+    let discussion = forecast_data.iter()
+        .map(|f| f.discussion.trim())
+        .find(|d| !d.is_empty())
+        .map(str::to_string);
+
+    let mut by_date: BTreeMap<NaiveDate, Vec<PollutantForecast>> = BTreeMap::new();
+    for forecast in forecast_data {
+        let Some(date) = parse_airnow_date(&forecast.date_forecast) else {
+            continue;
+        };
+        by_date.entry(date).or_default().push(PollutantForecast {
+            parameter: forecast.parameter_name.trim().to_string(),
+            aqi: (forecast.aqi >= 0).then_some(forecast.aqi),
+            category: forecast.category.name,
+            category_number: forecast.category.number,
+            action_day: forecast.action_day,
+        });
+    }
+
+    let days = by_date
         .into_iter()
-        .next()
-        .map(AirQualityIndex::from)
-        .ok_or_else(|| "No air quality data found for this zip code".to_string())
-}
\ No newline at end of file
+        .filter_map(|(date, pollutants)| {
+            let worst = pollutants.iter().max_by_key(|p| (p.category_number, p.aqi))?;
+            Some(ForecastDay {
+                date,
+                aqi: pollutants.iter().filter_map(|p| p.aqi).max(),
+                category: worst.category.clone(),
+                category_number: worst.category_number,
+                action_day: pollutants.iter().any(|p| p.action_day),
+                pollutants,
+            })
+        })
+        .collect();
+
+    Ok(AirQualityForecast { reporting_area, state_code, issued_on, discussion, days })
+}
+
+pub fn zip_fixture_name(zip_code: &str) -> String {
+    format!("observation_zip_{}", zip_code)
+}
+
+pub fn coords_fixture_name(lat: f64, lon: f64) -> String {
+    format!("observation_latlong_{}", fixture_coords(lat, lon))
+}
+
+fn forecast_date_suffix(date: Option<NaiveDate>) -> String {
+    date.map(|d| format!("_{}", d)).unwrap_or_default()
+}
+
+pub fn forecast_zip_fixture_name(zip_code: &str, date: Option<NaiveDate>) -> String {
+    format!("forecast_zip_{}{}", zip_code, forecast_date_suffix(date))
+}
+
+pub fn forecast_coords_fixture_name(lat: f64, lon: f64, date: Option<NaiveDate>) -> String {
+    format!("forecast_latlong_{}{}", fixture_coords(lat, lon), forecast_date_suffix(date))
+}
+
+pub struct AirNowClient {
+    http: Client,
+    api_key: String,
+    base_url: String,
+    distance_miles: u32,
+    recorder: Option<FixtureStore>, // Saves every successful response when set
+}
+
+impl AirNowClient {
+    pub fn new(api_key: String, base_url: String) -> Self {
+        AirNowClient {
+            http: Client::new(),
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            distance_miles: DEFAULT_DISTANCE_MILES,
+            recorder: None,
+        }
+    }
+
+    /// How far from the requested point AirNow may look for a reporting area
+    pub fn with_distance_miles(mut self, distance_miles: u32) -> Self {
+        self.distance_miles = distance_miles;
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: Option<FixtureStore>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    async fn fetch_body(&self, path: &str, query: &[(&str, String)], fixture_name: &str) -> Result<String, ProviderError> {
+        if self.api_key.trim().is_empty() {
+            return Err(ProviderError::MissingApiKey(PROVIDER));
+        }
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.http.get(&url)
+            .query(&[("format", "application/json"), ("API_KEY", self.api_key.as_str())])
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest(PROVIDER, e))?;
+
+        if !response.status().is_success() {
+            return Err(ProviderError::from_status(PROVIDER, response.status()));
+        }
+
+        let body = response.text().await
+            .map_err(|e| ProviderError::from_reqwest(PROVIDER, e))?;
+
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.write(AIRNOW_FIXTURES, fixture_name, &body) {
+                eprintln!("Failed to record AirNow fixture: {}", e);
+            }
+        }
+
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl AirQualityProvider for AirNowClient {
+    async fn current_by_zip(&self, zip_code: &str) -> Result<AirQualityIndex, ProviderError> {
+        let body = self.fetch_body(
+            "/aq/observation/zipCode/current/",
+            &[("zipCode", zip_code.to_string()), ("distance", self.distance_miles.to_string())],
+            &zip_fixture_name(zip_code),
+        ).await?;
+
+        parse_current_observations(&body, "No air quality data found for this zip code")
+    }
+
+    async fn current_by_coords(&self, lat: f64, lon: f64) -> Result<AirQualityIndex, ProviderError> {
+        let body = self.fetch_body(
+            "/aq/observation/latLong/current/",
+            &[
+                ("latitude", lat.to_string()),
+                ("longitude", lon.to_string()),
+                ("distance", self.distance_miles.to_string()),
+            ],
+            &coords_fixture_name(lat, lon),
+        ).await?;
+
+        parse_current_observations(&body, "No air quality data found near these coordinates")
+    }
+
+    async fn forecast_by_zip(&self, zip_code: &str, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+        let mut query = vec![("zipCode", zip_code.to_string()), ("distance", self.distance_miles.to_string())];
+        // AirNow defaults to today's forecast when no date is given
+        query.extend(date.map(|d| ("date", d.to_string())));
+
+        let body = self.fetch_body(
+            "/aq/forecast/zipCode/",
+            &query,
+            &forecast_zip_fixture_name(zip_code, date),
+        ).await?;
+
+        parse_forecast(&body, "No air quality forecast found for this zip code")
+    }
+
+    async fn forecast_by_coords(&self, lat: f64, lon: f64, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+        let mut query = vec![
+            ("latitude", lat.to_string()),
+            ("longitude", lon.to_string()),
+            ("distance", self.distance_miles.to_string()),
+        ];
+        query.extend(date.map(|d| ("date", d.to_string())));
+
+        let body = self.fetch_body(
+            "/aq/forecast/latLong/",
+            &query,
+            &forecast_coords_fixture_name(lat, lon, date),
+        ).await?;
+
+        parse_forecast(&body, "No air quality forecast found near these coordinates")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observed_at_converts_local_time_to_utc() {
+        let json = r#"{
+            "DateObserved": "2025-06-06 ",
+            "HourObserved": 14,
+            "LocalTimeZone": "PDT",
+            "ReportingArea": "Klamath Falls",
+            "StateCode": "OR",
+            "Latitude": 42.2249,
+            "Longitude": -121.7817,
+            "ParameterName": "PM2.5",
+            "AQI": 42,
+            "Category": { "Number": 1, "Name": "Good" }
+        }"#;
+        let response: AirNowResponse = serde_json::from_str(json).expect("Failed to parse observation");
+
+        let observed_at = response.observed_at().expect("Observation time should parse");
+        assert_eq!(observed_at.to_rfc3339(), "2025-06-06T21:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_keeps_every_pollutant_and_picks_the_dominant_one() {
+        let body = include_str!("../../fixtures/airnow/observation_default.json");
+        let index = parse_current_observations(body, "none").expect("Failed to parse observations");
+
+        let parameters: Vec<&str> = index.pollutants.iter().map(|p| p.parameter.as_str()).collect();
+        assert_eq!(parameters, ["O3", "PM2.5", "PM10"]);
+        assert_eq!(index.dominant_pollutant.as_deref(), Some("PM2.5"));
+        assert_eq!(index.aqi, 112);
+        assert_eq!(index.category, "Unhealthy for Sensitive Groups");
+        assert_eq!(index.location().as_deref(), Some("Klamath Falls, OR"));
+
+        assert!(matches!(parse_current_observations("[]", "none"), Err(ProviderError::NoData(_))));
+    }
+
+    #[test]
+    fn test_parse_forecast_groups_pollutants_by_day() {
+        let body = include_str!("../../fixtures/airnow/forecast_default.json");
+        let forecast = parse_forecast(body, "none").expect("Failed to parse forecast");
+
+        assert_eq!(forecast.location(), "Klamath Falls, OR");
+        assert!(forecast.discussion.as_deref().unwrap().starts_with("Smoke from the Flat Fire"));
+
+        let dates: Vec<String> = forecast.days.iter().map(|d| d.date.to_string()).collect();
+        assert_eq!(dates, ["2025-08-14", "2025-08-15", "2025-08-16"]);
+
+        let today = &forecast.days[0];
+        assert_eq!(today.aqi, Some(118));
+        assert_eq!(today.category_number, 3);
+        assert!(today.action_day);
+
+        // Category-only forecasts have no AQI number
+        let tomorrow = &forecast.days[1];
+        assert_eq!(tomorrow.aqi, None);
+        assert_eq!(tomorrow.category, "Unhealthy for Sensitive Groups");
+        assert!(!tomorrow.action_day);
+    }
+}
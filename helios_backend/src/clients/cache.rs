@@ -0,0 +1,107 @@
+// src/clients/cache.rs
+
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{AirQualityForecast, ProviderError};
+
+/// Caches AirNow forecasts per reporting area. Every zip code or coordinate
+/// that resolved to an area shares that area's entry until it expires.
+pub struct ForecastCache {
+    ttl: Duration,
+    inner: Mutex<CacheInner>,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    forecasts: HashMap<String, (Instant, AirQualityForecast)>, // area key -> forecast
+    areas: HashMap<String, String>,                            // request key -> area key
+}
+
+impl ForecastCache {
+    pub fn new(ttl: Duration) -> Self {
+        ForecastCache {
+            ttl,
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    /// Return the cached forecast for `request_key` if it is fresh, otherwise call
+    /// `fetch` and cache the result. The flag is true when the cache was used.
+    pub async fn get_or_fetch<F, Fut>(
+        &self,
+        request_key: &str,
+        date: Option<NaiveDate>,
+        fetch: F,
+    ) -> Result<(AirQualityForecast, bool), ProviderError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<AirQualityForecast, ProviderError>>,
+    {
+        let request_key = format!("{}|{}", request_key, date_key(date));
+        if let Some(forecast) = self.get(&request_key) {
+            return Ok((forecast, true));
+        }
+
+        let forecast = fetch().await?;
+        let area_key = format!("{}|{}", forecast.location(), date_key(date));
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.areas.insert(request_key, area_key.clone());
+        inner.forecasts.insert(area_key, (Instant::now(), forecast.clone()));
+        // Drop anything expired so the maps do not grow with every date and location requested
+        let ttl = self.ttl;
+        let CacheInner { forecasts, areas } = &mut *inner;
+        forecasts.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+        areas.retain(|_, area_key| forecasts.contains_key(area_key));
+
+        Ok((forecast, false))
+    }
+
+    fn get(&self, request_key: &str) -> Option<AirQualityForecast> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let area_key = inner.areas.get(request_key)?;
+        let (fetched_at, forecast) = inner.forecasts.get(area_key)?;
+        (fetched_at.elapsed() < self.ttl).then(|| forecast.clone())
+    }
+}
+
+fn date_key(date: Option<NaiveDate>) -> String {
+    date.map(|d| d.to_string()).unwrap_or_else(|| "today".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::airnow::parse_forecast;
+
+    fn sample_forecast() -> AirQualityForecast {
+        parse_forecast(include_str!("../../fixtures/airnow/forecast_default.json"), "none").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_forecasts_are_cached_per_area_and_date() {
+        let cache = ForecastCache::new(Duration::from_secs(60));
+
+        let (_, cached) = cache.get_or_fetch("zip:97601", None, || async { Ok(sample_forecast()) }).await.unwrap();
+        assert!(!cached);
+        let (_, cached) = cache.get_or_fetch("zip:97601", None, || async { Ok(sample_forecast()) }).await.unwrap();
+        assert!(cached);
+
+        // A different date is a different forecast
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14);
+        let (_, cached) = cache.get_or_fetch("zip:97601", date, || async { Ok(sample_forecast()) }).await.unwrap();
+        assert!(!cached);
+
+        let expired = ForecastCache::new(Duration::ZERO);
+        expired.get_or_fetch("zip:97601", None, || async { Ok(sample_forecast()) }).await.unwrap();
+        let (_, cached) = expired.get_or_fetch("zip:97601", None, || async { Ok(sample_forecast()) }).await.unwrap();
+        assert!(!cached);
+        expired.get_or_fetch("zip:97701", None, || async { Ok(sample_forecast()) }).await.unwrap();
+        let inner = expired.inner.lock().unwrap();
+        assert!(inner.forecasts.is_empty() && inner.areas.is_empty(), "expired entries are evicted");
+    }
+}
@@ -0,0 +1,66 @@
+// src/clients/error.rs
+
+use thiserror::Error;
+
+/// Why a call to an upstream provider failed
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("{0} API key is not configured")]
+    MissingApiKey(&'static str),
+    #[error("{0} request timed out")]
+    Timeout(&'static str),
+    #[error("{provider} request failed: {message}")]
+    Request { provider: &'static str, message: String },
+    #[error("{provider} request failed with status: {status}")]
+    Http { provider: &'static str, status: u16 },
+    #[error("{0} rate limit exceeded")]
+    RateLimited(&'static str),
+    #[error("Failed to parse {provider} response: {message}")]
+    Parse { provider: &'static str, message: String },
+    #[error("{0}")]
+    NoData(String),
+    #[error("{0}")]
+    Fixture(String),
+}
+
+impl ProviderError {
+    pub fn from_reqwest(provider: &'static str, err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ProviderError::Timeout(provider)
+        } else {
+            ProviderError::Request { provider, message: err.to_string() }
+        }
+    }
+
+    pub fn from_status(provider: &'static str, status: reqwest::StatusCode) -> Self {
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            ProviderError::RateLimited(provider)
+        } else {
+            ProviderError::Http { provider, status: status.as_u16() }
+        }
+    }
+
+    /// Transient failures that are worth another attempt
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::Timeout(_) | ProviderError::Request { .. } => true,
+            ProviderError::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_transient_failures_are_retryable() {
+        assert!(ProviderError::Timeout("AirNow").is_retryable());
+        assert!(ProviderError::Http { provider: "AirNow", status: 503 }.is_retryable());
+        assert!(!ProviderError::Http { provider: "AirNow", status: 401 }.is_retryable());
+        assert!(!ProviderError::RateLimited("AirNow").is_retryable());
+        assert!(!ProviderError::MissingApiKey("AirNow").is_retryable());
+        assert!(!ProviderError::NoData("No air quality data found".to_string()).is_retryable());
+    }
+}
@@ -0,0 +1,136 @@
+// src/clients/fixtures.rs
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::fs;
+use std::path::PathBuf;
+
+use super::airnow::{self, AirQualityForecast, AirQualityIndex};
+use super::weather::{self, WeatherData, WeatherForecast};
+use super::{AirQualityProvider, ProviderError, WeatherProvider};
+
+// Sub-directories of the fixture root, one per upstream API
+pub const AIRNOW_FIXTURES: &str = "airnow";
+pub const WEATHER_FIXTURES: &str = "openweathermap";
+
+/// Coordinates are rounded in fixture names so nearby requests share a recording
+pub fn fixture_coords(lat: f64, lon: f64) -> String {
+    format!("{:.2}_{:.2}", lat, lon)
+}
+
+/// Canned upstream response bodies stored as `<dir>/<provider>/<name>.json`
+#[derive(Debug, Clone)]
+pub struct FixtureStore {
+    dir: PathBuf,
+}
+
+impl FixtureStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FixtureStore { dir: dir.into() }
+    }
+
+    fn path(&self, provider: &str, name: &str) -> PathBuf {
+        self.dir.join(provider).join(format!("{}.json", name))
+    }
+
+    /// Read a recorded body, falling back to the provider's `<kind>_default.json`
+    /// so arbitrary coordinates still resolve offline.
+    pub fn read(&self, provider: &str, name: &str) -> Result<String, String> {
+        let exact = self.path(provider, name);
+        if exact.exists() {
+            return fs::read_to_string(&exact)
+                .map_err(|e| format!("Failed to read fixture {}: {}", exact.display(), e));
+        }
+
+        let kind = name.split('_').next().unwrap_or(name);
+        let fallback = self.path(provider, &format!("{}_default", kind));
+        fs::read_to_string(&fallback)
+            .map_err(|e| format!("No fixture for {} (tried {}): {}", name, fallback.display(), e))
+    }
+
+    pub fn write(&self, provider: &str, name: &str, body: &str) -> Result<(), String> {
+        let path = self.path(provider, name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, body)
+            .map_err(|e| format!("Failed to write fixture {}: {}", path.display(), e))
+    }
+}
+
+/// Serves recorded responses instead of calling AirNow and OpenWeatherMap
+pub struct FixtureProvider {
+    store: FixtureStore,
+}
+
+impl FixtureProvider {
+    pub fn new(store: FixtureStore) -> Self {
+        FixtureProvider { store }
+    }
+}
+
+#[async_trait]
+impl AirQualityProvider for FixtureProvider {
+    async fn current_by_zip(&self, zip_code: &str) -> Result<AirQualityIndex, ProviderError> {
+        let body = self.store.read(AIRNOW_FIXTURES, &airnow::zip_fixture_name(zip_code))
+            .map_err(ProviderError::Fixture)?;
+        airnow::parse_current_observations(&body, "No air quality data found for this zip code")
+    }
+
+    async fn current_by_coords(&self, lat: f64, lon: f64) -> Result<AirQualityIndex, ProviderError> {
+        let body = self.store.read(AIRNOW_FIXTURES, &airnow::coords_fixture_name(lat, lon))
+            .map_err(ProviderError::Fixture)?;
+        airnow::parse_current_observations(&body, "No air quality data found near these coordinates")
+    }
+
+    async fn forecast_by_zip(&self, zip_code: &str, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+        let body = self.store.read(AIRNOW_FIXTURES, &airnow::forecast_zip_fixture_name(zip_code, date))
+            .map_err(ProviderError::Fixture)?;
+        airnow::parse_forecast(&body, "No air quality forecast found for this zip code")
+    }
+
+    async fn forecast_by_coords(&self, lat: f64, lon: f64, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+        let body = self.store.read(AIRNOW_FIXTURES, &airnow::forecast_coords_fixture_name(lat, lon, date))
+            .map_err(ProviderError::Fixture)?;
+        airnow::parse_forecast(&body, "No air quality forecast found near these coordinates")
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for FixtureProvider {
+    async fn current(&self, lat: f64, lon: f64) -> Result<WeatherData, ProviderError> {
+        let body = self.store.read(WEATHER_FIXTURES, &weather::weather_fixture_name(lat, lon))
+            .map_err(ProviderError::Fixture)?;
+        weather::parse_current_weather(&body)
+    }
+
+    async fn forecast(&self, lat: f64, lon: f64) -> Result<WeatherForecast, ProviderError> {
+        let body = self.store.read(WEATHER_FIXTURES, &weather::forecast_fixture_name(lat, lon))
+            .map_err(ProviderError::Fixture)?;
+        weather::parse_forecast(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_fixture_round_trips_and_falls_back_to_default() {
+        let dir = std::env::temp_dir().join(format!("helios_fixtures_{}", std::process::id()));
+        let store = FixtureStore::new(&dir);
+
+        store.write(WEATHER_FIXTURES, "weather_default", "{\"default\": true}").unwrap();
+        store.write(WEATHER_FIXTURES, &weather::weather_fixture_name(44.1, -121.7), "{}").unwrap();
+
+        assert_eq!(store.read(WEATHER_FIXTURES, "weather_44.10_-121.70").unwrap(), "{}");
+        assert_eq!(
+            store.read(WEATHER_FIXTURES, "weather_10.00_10.00").unwrap(),
+            "{\"default\": true}"
+        );
+        assert!(store.read(AIRNOW_FIXTURES, "observation_zip_00000").is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,5 +1,72 @@
 pub mod airnow;
+pub mod cache;
+pub mod error;
+pub mod fixtures;
 pub mod weather;
 
-pub use airnow::get_aqi_by_zip;
-pub use weather::get_weather_by_coords;
\ No newline at end of file
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::sync::Arc;
+
+use crate::config::{Config, ProviderConfig, ProviderMode};
+
+pub use airnow::{AirNowClient, AirQualityForecast, AirQualityIndex};
+pub use cache::ForecastCache;
+pub use error::ProviderError;
+pub use fixtures::{FixtureProvider, FixtureStore};
+pub use weather::{OpenWeatherMapClient, WeatherData, WeatherForecast};
+
+/// A source of current air quality observations
+#[async_trait]
+pub trait AirQualityProvider: Send + Sync {
+    async fn current_by_zip(&self, zip_code: &str) -> Result<AirQualityIndex, ProviderError>;
+
+    async fn current_by_coords(&self, lat: f64, lon: f64) -> Result<AirQualityIndex, ProviderError>;
+
+    /// Multi-day forecast issued on `date`, or today when `None`
+    async fn forecast_by_zip(&self, zip_code: &str, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError>;
+
+    async fn forecast_by_coords(&self, lat: f64, lon: f64, date: Option<NaiveDate>) -> Result<AirQualityForecast, ProviderError>;
+}
+
+/// A source of current weather conditions
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    async fn current(&self, lat: f64, lon: f64) -> Result<WeatherData, ProviderError>;
+
+    /// Forecast conditions in 3 hour steps for about the next 5 days
+    async fn forecast(&self, lat: f64, lon: f64) -> Result<WeatherForecast, ProviderError>;
+}
+
+pub struct Providers {
+    pub air_quality: Arc<dyn AirQualityProvider>,
+    pub weather: Arc<dyn WeatherProvider>,
+}
+
+/// Build the air quality and weather providers selected by `PROVIDER_MODE`.
+/// Replay mode needs no API keys; live and record modes do.
+pub fn providers_from_env(provider_config: &ProviderConfig) -> Result<Providers, Box<dyn std::error::Error>> {
+    let store = FixtureStore::new(&provider_config.fixtures_dir);
+
+    if provider_config.mode == ProviderMode::Replay {
+        let provider = Arc::new(FixtureProvider::new(store));
+        return Ok(Providers {
+            air_quality: provider.clone(),
+            weather: provider,
+        });
+    }
+
+    let config = Config::from_env()?;
+    let recorder = (provider_config.mode == ProviderMode::Record).then_some(store);
+
+    let air_quality = AirNowClient::new(config.airnow_api_key, config.airnow_base_url)
+        .with_distance_miles(config.airnow_distance_miles)
+        .with_recorder(recorder.clone());
+    let weather = OpenWeatherMapClient::new(config.weather_api_key, config.weather_base_url)
+        .with_recorder(recorder);
+
+    Ok(Providers {
+        air_quality: Arc::new(air_quality),
+        weather: Arc::new(weather),
+    })
+}
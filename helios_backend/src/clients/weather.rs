@@ -1,11 +1,32 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
 use serde::Deserialize;
 
+use crate::units::{Precipitation, Speed, Temperature};
+
+use super::fixtures::{fixture_coords, FixtureStore, WEATHER_FIXTURES};
+use super::{ProviderError, WeatherProvider};
+
+pub const OPENWEATHERMAP_BASE_URL: &str = "https://api.openweathermap.org";
+const PROVIDER: &str = "OpenWeatherMap";
+
 #[derive(Deserialize)]
 pub struct WeatherResponse {
     pub main: Main,
     pub wind: Wind,
+    pub rain: Option<RecentVolume>, // Omitted when it has not rained
+    pub snow: Option<RecentVolume>,
+    pub dt: i64, // Observation time, unix seconds UTC
 }
 
+#[derive(Deserialize)]
+pub struct RecentVolume {
+    #[serde(rename = "1h", default)]
+    pub one_hour: f64, // mm
+}
+
+// Requested with units=metric, so temperatures are °C and speeds m/s
 #[derive(Deserialize)]
 pub struct Main {
     pub temp: f64,
@@ -18,39 +39,233 @@ pub struct Wind {
     pub deg: f64,
 }
 
+/// 5 day / 3 hour forecast (`/data/2.5/forecast`)
+#[derive(Deserialize)]
+pub struct ForecastResponse {
+    pub list: Vec<ForecastItem>,
+    pub city: ForecastCity,
+}
+
+#[derive(Deserialize)]
+pub struct ForecastItem {
+    pub dt: i64, // Start of the 3 hour step, unix seconds UTC
+    pub main: Main,
+    pub wind: Wind,
+    pub rain: Option<Volume>,
+    pub snow: Option<Volume>, // Water equivalent
+    #[serde(default)]
+    pub pop: f64, // Probability of precipitation, 0-1
+}
+
+#[derive(Deserialize)]
+pub struct Volume {
+    #[serde(rename = "3h", default)]
+    pub three_hours: f64, // mm
+}
+
+#[derive(Deserialize)]
+pub struct ForecastCity {
+    #[serde(default)]
+    pub timezone: i32, // Shift from UTC in seconds
+}
+
 // Add this struct for easier access to weather data
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WeatherData {
-    pub temperature: f64,
+    pub temperature: Temperature,
     pub humidity: u8,
-    pub wind_speed: f64,
+    pub wind_speed: Speed,
     pub wind_direction: f64,
+    pub precipitation_last_hour: Option<Precipitation>, // Rain + snow; None when the source does not measure it
+    pub observed_at: DateTime<Utc>,
 }
 
 impl From<WeatherResponse> for WeatherData {
     fn from(response: WeatherResponse) -> Self {
+        // OpenWeatherMap leaves out rain and snow when nothing fell, which is an observed dry
+        // hour. Stored as 0 mm so it counts toward precipitation coverage.
+        let precipitation = response.rain.map_or(0.0, |r| r.one_hour)
+            + response.snow.map_or(0.0, |s| s.one_hour);
         WeatherData {
-            temperature: response.main.temp,
+            temperature: Temperature::from_celsius(response.main.temp),
             humidity: response.main.humidity,
-            wind_speed: response.wind.speed,
+            wind_speed: Speed::from_meters_per_second(response.wind.speed),
             wind_direction: response.wind.deg,
+            precipitation_last_hour: Some(Precipitation::from_millimeters(precipitation)),
+            observed_at: DateTime::from_timestamp(response.dt, 0).unwrap_or_else(Utc::now),
         }
     }
 }
 
-pub async fn get_weather_by_coords(lat: f64, lon: f64) -> Result<WeatherData, String> {
-    let api_key = std::env::var("WEATHER_API_KEY").map_err(|_| "WEATHER_API_KEY must be set".to_string())?;
-    let url = format!("https://api.openweathermap.org/data/2.5/weather?lat={}&lon={}&appid={}&units=metric", lat, lon, api_key);
-    
-    let response = reqwest::get(&url).await
-        .map_err(|e| format!("Weather API request failed: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("Weather API request failed with status: {}", response.status()));
+/// Conditions expected at the start of one forecast step
+#[derive(Debug, Clone)]
+pub struct WeatherForecastStep {
+    pub weather: WeatherData, // observed_at is the forecast time
+    pub precipitation: Precipitation, // Expected over the 3 hour step
+    pub precipitation_probability: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeatherForecast {
+    pub utc_offset_seconds: i32, // Local time at the forecast location
+    pub steps: Vec<WeatherForecastStep>,
+}
+
+impl From<ForecastResponse> for WeatherForecast {
+    fn from(response: ForecastResponse) -> Self {
+        let steps = response.list
+            .into_iter()
+            .map(|item| {
+                let precipitation = item.rain.map_or(0.0, |r| r.three_hours)
+                    + item.snow.map_or(0.0, |s| s.three_hours);
+                WeatherForecastStep {
+                    weather: WeatherData {
+                        temperature: Temperature::from_celsius(item.main.temp),
+                        humidity: item.main.humidity,
+                        wind_speed: Speed::from_meters_per_second(item.wind.speed),
+                        wind_direction: item.wind.deg,
+                        precipitation_last_hour: None, // See precipitation below for the step total
+                        observed_at: DateTime::from_timestamp(item.dt, 0).unwrap_or_else(Utc::now),
+                    },
+                    precipitation: Precipitation::from_millimeters(precipitation),
+                    precipitation_probability: item.pop,
+                }
+            })
+            .collect();
+
+        WeatherForecast {
+            utc_offset_seconds: response.city.timezone,
+            steps,
+        }
     }
-    
-    let weather_response: WeatherResponse = response.json().await
-        .map_err(|e| format!("Failed to parse weather response: {}", e))?;
-    
+}
+
+/// Parse an OpenWeatherMap current-conditions response body
+pub fn parse_current_weather(body: &str) -> Result<WeatherData, ProviderError> {
+    let weather_response: WeatherResponse = serde_json::from_str(body)
+        .map_err(|e| ProviderError::Parse { provider: PROVIDER, message: e.to_string() })?;
+
     Ok(WeatherData::from(weather_response))
-}
\ No newline at end of file
+}
+
+/// Parse an OpenWeatherMap 5 day / 3 hour forecast response body
+pub fn parse_forecast(body: &str) -> Result<WeatherForecast, ProviderError> {
+    let forecast_response: ForecastResponse = serde_json::from_str(body)
+        .map_err(|e| ProviderError::Parse { provider: PROVIDER, message: e.to_string() })?;
+
+    if forecast_response.list.is_empty() {
+        return Err(ProviderError::NoData("No weather forecast returned for these coordinates".to_string()));
+    }
+
+    Ok(WeatherForecast::from(forecast_response))
+}
+
+pub fn weather_fixture_name(lat: f64, lon: f64) -> String {
+    format!("weather_{}", fixture_coords(lat, lon))
+}
+
+pub fn forecast_fixture_name(lat: f64, lon: f64) -> String {
+    format!("forecast_{}", fixture_coords(lat, lon))
+}
+
+pub struct OpenWeatherMapClient {
+    http: Client,
+    api_key: String,
+    base_url: String,
+    recorder: Option<FixtureStore>, // Saves every successful response when set
+}
+
+impl OpenWeatherMapClient {
+    pub fn new(api_key: String, base_url: String) -> Self {
+        OpenWeatherMapClient {
+            http: Client::new(),
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            recorder: None,
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: Option<FixtureStore>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+}
+
+impl OpenWeatherMapClient {
+    async fn fetch_body(&self, path: &str, lat: f64, lon: f64, fixture_name: &str) -> Result<String, ProviderError> {
+        if self.api_key.trim().is_empty() {
+            return Err(ProviderError::MissingApiKey(PROVIDER));
+        }
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.http.get(&url)
+            .query(&[
+                ("lat", lat.to_string()),
+                ("lon", lon.to_string()),
+                ("appid", self.api_key.clone()),
+                ("units", "metric".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest(PROVIDER, e))?;
+
+        if !response.status().is_success() {
+            return Err(ProviderError::from_status(PROVIDER, response.status()));
+        }
+
+        let body = response.text().await
+            .map_err(|e| ProviderError::from_reqwest(PROVIDER, e))?;
+
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.write(WEATHER_FIXTURES, fixture_name, &body) {
+                eprintln!("Failed to record weather fixture: {}", e);
+            }
+        }
+
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for OpenWeatherMapClient {
+    async fn current(&self, lat: f64, lon: f64) -> Result<WeatherData, ProviderError> {
+        let body = self.fetch_body("/data/2.5/weather", lat, lon, &weather_fixture_name(lat, lon)).await?;
+        parse_current_weather(&body)
+    }
+
+    async fn forecast(&self, lat: f64, lon: f64) -> Result<WeatherForecast, ProviderError> {
+        let body = self.fetch_body("/data/2.5/forecast", lat, lon, &forecast_fixture_name(lat, lon)).await?;
+        parse_forecast(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forecast_sums_rain_and_snow_per_step() {
+        let body = include_str!("../../fixtures/openweathermap/forecast_default.json");
+        let forecast = parse_forecast(body).expect("Failed to parse forecast");
+
+        assert_eq!(forecast.steps.len(), 40);
+        assert_eq!(forecast.utc_offset_seconds, -25200);
+        assert_eq!(forecast.steps[0].weather.observed_at.to_rfc3339(), "2025-08-14T21:00:00+00:00");
+        assert!((forecast.steps[0].weather.temperature.celsius() - 32.6).abs() < 1e-9);
+
+        let total: f64 = forecast.steps.iter().map(|s| s.precipitation.millimeters()).sum();
+        assert!((total - 5.88).abs() < 1e-9, "expected 5.88 mm, got {}", total);
+    }
+
+    #[test]
+    fn test_current_weather_without_rain_or_snow_is_a_dry_hour() {
+        let dry = r#"{"main": {"temp": 20.0, "humidity": 40}, "wind": {"speed": 3.0, "deg": 180.0}, "dt": 1755205200}"#;
+        let weather = parse_current_weather(dry).unwrap();
+        assert_eq!(weather.precipitation_last_hour.map(|p| p.millimeters()), Some(0.0));
+
+        let reported = r#"{"main": {"temp": 1.0, "humidity": 90}, "wind": {"speed": 3.0, "deg": 180.0}, "dt": 1755205200,
+                      "rain": {"1h": 0.4}, "snow": {"1h": 0.1}}"#;
+        let weather = parse_current_weather(reported).unwrap();
+        assert!((weather.precipitation_last_hour.unwrap().millimeters() - 0.5).abs() < 1e-9);
+    }
+}
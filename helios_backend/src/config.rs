@@ -1,26 +1,268 @@
 use serde::Deserialize;
 use dotenvy::dotenv;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::clients::airnow::{AIRNOW_BASE_URL, DEFAULT_DISTANCE_MILES};
+use crate::clients::weather::OPENWEATHERMAP_BASE_URL;
+use crate::grid::{DEFAULT_RESOLUTION, LATTICE_RESOLUTIONS};
+use crate::indices::kbdi::{KBDI_DEFAULT_INITIAL, KBDI_MAX};
+use crate::indices::nfdrs::FuelModel;
+use crate::ml::registry::LoadOptions;
+use crate::ml::OutOfRangePolicy;
+use crate::units::Precipitation;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    #[allow(dead_code)]
     pub airnow_api_key: String,
-    #[allow(dead_code)]
     pub weather_api_key: String,
+    pub airnow_base_url: String,
+    pub weather_base_url: String,
+    pub airnow_distance_miles: u32, // Search radius for AirNow lookups
 }
 
 impl Config {
-    #[allow(dead_code)]
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
         dotenv().ok();
         
         let airnow_api_key = env::var("AIRNOW_API_KEY")?;
         let weather_api_key = env::var("WEATHER_API_KEY")?;
+        let airnow_base_url = env::var("AIRNOW_BASE_URL").unwrap_or_else(|_| AIRNOW_BASE_URL.to_string());
+        let weather_base_url = env::var("WEATHER_BASE_URL").unwrap_or_else(|_| OPENWEATHERMAP_BASE_URL.to_string());
+        let airnow_distance_miles = match env::var("AIRNOW_DISTANCE_MILES") {
+            Ok(v) => v.parse::<u32>()
+                .map_err(|_| format!("Invalid AIRNOW_DISTANCE_MILES '{}', expected whole miles", v))?,
+            Err(_) => DEFAULT_DISTANCE_MILES,
+        };
         
         Ok(Config {
             airnow_api_key,
             weather_api_key,
+            airnow_base_url,
+            weather_base_url,
+            airnow_distance_miles,
+        })
+    }
+}
+
+/// Where upstream data comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderMode {
+    Live,   // Call AirNow and OpenWeatherMap
+    Record, // Call them and save each response as a fixture
+    Replay, // Serve saved fixtures, no network or API keys needed
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderConfig {
+    pub mode: ProviderMode,
+    pub fixtures_dir: String,
+    pub forecast_cache_ttl: Duration,
+}
+
+impl ProviderConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let mode = match env::var("PROVIDER_MODE").unwrap_or_default().to_lowercase().as_str() {
+            "" | "live" => ProviderMode::Live,
+            "record" => ProviderMode::Record,
+            "replay" => ProviderMode::Replay,
+            other => return Err(format!("Unknown PROVIDER_MODE '{}', expected live, record or replay", other)),
+        };
+        let fixtures_dir = env::var("FIXTURES_DIR").unwrap_or_else(|_| "fixtures".to_string());
+        let forecast_cache_secs = env::var("FORECAST_CACHE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3600); // AirNow issues forecasts once or twice a day
+
+        Ok(ProviderConfig {
+            mode,
+            fixtures_dir,
+            forecast_cache_ttl: Duration::from_secs(forecast_cache_secs),
+        })
+    }
+}
+
+/// Settings for the background ingestion worker
+#[derive(Debug, Clone)]
+pub struct IngestionConfig {
+    pub enabled: bool,
+    pub interval: Duration,
+}
+
+impl IngestionConfig {
+    pub fn from_env() -> Self {
+        let enabled = env::var("INGESTION_ENABLED")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+            .unwrap_or(true);
+        let interval_secs = env::var("INGESTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(900); // AirNow updates hourly; poll every 15 minutes
+
+        IngestionConfig {
+            enabled,
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+
+    /// Stored readings older than two polling intervals are treated as stale
+    pub fn max_reading_age(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.interval * 2).unwrap_or_else(|_| chrono::Duration::hours(1))
+    }
+}
+
+/// In-memory caching of rendered map tiles
+#[derive(Debug, Clone)]
+pub struct TileConfig {
+    pub cache_ttl: Duration,
+    pub max_cached: usize,
+}
+
+impl TileConfig {
+    pub fn from_env() -> Self {
+        let cache_secs = env::var("TILE_CACHE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60); // Readings and risk cells change every few minutes at most
+        let max_cached = env::var("TILE_CACHE_MAX")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(2000);
+
+        TileConfig {
+            cache_ttl: Duration::from_secs(cache_secs),
+            max_cached,
+        }
+    }
+}
+
+/// Settings for the background job that scores the stored risk map layer
+#[derive(Debug, Clone)]
+pub struct RiskLayerConfig {
+    pub enabled: bool,
+    pub interval: Duration,
+    pub resolution: f64, // Cell size in degrees, one of grid::LATTICE_RESOLUTIONS
+}
+
+impl RiskLayerConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let enabled = env::var("RISK_LAYER_ENABLED")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+            .unwrap_or(true);
+        let interval_secs = env::var("RISK_LAYER_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(900); // Follows the ingestion worker's readings
+        let resolution = match env::var("RISK_LAYER_RESOLUTION") {
+            Ok(v) => v
+                .parse::<f64>()
+                .ok()
+                .filter(|resolution| LATTICE_RESOLUTIONS.contains(resolution))
+                .ok_or_else(|| format!("Invalid RISK_LAYER_RESOLUTION '{}', expected one of {:?}", v, LATTICE_RESOLUTIONS))?,
+            Err(_) => DEFAULT_RESOLUTION,
+        };
+
+        Ok(RiskLayerConfig {
+            enabled,
+            interval: Duration::from_secs(interval_secs),
+            resolution,
+        })
+    }
+}
+
+/// Settings for the daily fire danger indices
+#[derive(Debug, Clone)]
+pub struct IndicesConfig {
+    pub enabled: bool,
+    pub kbdi_initial: f64,                   // Starting KBDI for a sensor with no history, 0-800
+    pub mean_annual_rainfall: Precipitation, // Climate input to the KBDI drying rate
+    pub fuel_model: FuelModel,               // NFDRS fuel model used unless a request names another
+    pub herbaceous_moisture: f64,            // Live fuel moistures for NFDRS, %
+    pub woody_moisture: f64,
+}
+
+impl IndicesConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let enabled = env::var("INDICES_ENABLED")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+            .unwrap_or(true);
+        let kbdi_initial = env::var("KBDI_INITIAL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(KBDI_DEFAULT_INITIAL)
+            .clamp(0.0, KBDI_MAX);
+        let mean_annual_rainfall_in = env::var("KBDI_MEAN_ANNUAL_RAINFALL_IN")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|inches| *inches > 0.0)
+            .unwrap_or(20.0);
+        let fuel_model = match env::var("NFDRS_FUEL_MODEL") {
+            Ok(model) => model.parse::<FuelModel>()?,
+            Err(_) => FuelModel::G, // Short-needle conifer, typical of the Klamath Basin forests
+        };
+        // Mid-summer defaults: grasses mostly cured, shrubs well below green-up moisture
+        let herbaceous_moisture = env::var("NFDRS_HERBACEOUS_MOISTURE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(60.0)
+            .clamp(30.0, 250.0);
+        let woody_moisture = env::var("NFDRS_WOODY_MOISTURE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(90.0)
+            .clamp(60.0, 200.0);
+
+        Ok(IndicesConfig {
+            enabled,
+            kbdi_initial,
+            mean_annual_rainfall: Precipitation::from_inches(mean_annual_rainfall_in),
+            fuel_model,
+            herbaceous_moisture,
+            woody_moisture,
+        })
+    }
+}
+
+/// Where risk models are loaded from and who may swap them
+#[derive(Debug, Clone)]
+pub struct ModelConfig {
+    pub registry_dir: PathBuf,
+    pub admin_token: Option<String>, // Admin endpoints are disabled without one
+    pub out_of_range: OutOfRangePolicy, // For inputs outside a model's training ranges
+    pub sessions: usize,                // Inference sessions per loaded model
+}
+
+impl ModelConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let registry_dir = env::var("MODEL_REGISTRY_DIR").unwrap_or_else(|_| "models".to_string());
+        let admin_token = env::var("ADMIN_TOKEN").ok().filter(|token| !token.is_empty());
+        let out_of_range = match env::var("MODEL_OUT_OF_RANGE") {
+            Ok(policy) => policy.parse::<OutOfRangePolicy>()?,
+            Err(_) => OutOfRangePolicy::Clamp,
+        };
+        // One session per core, up to 4; the model is small and runs are short
+        let sessions = env::var("MODEL_SESSIONS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|sessions| *sessions > 0)
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()).min(4));
+
+        Ok(ModelConfig {
+            registry_dir: PathBuf::from(registry_dir),
+            admin_token,
+            out_of_range,
+            sessions,
         })
     }
-}
\ No newline at end of file
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            out_of_range: self.out_of_range,
+            sessions: self.sessions,
+        }
+    }
+}
@@ -1,5 +1,19 @@
-use sqlx::{Pool, Postgres, Error as SqlxError, Row};
-use crate::models::{Sensor, SensorLocation, NewSensor};
+use sqlx::{PgConnection, Pool, Postgres, Error as SqlxError, QueryBuilder, Row};
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::models::{
+    Sensor, SensorLocation, NewSensor, SensorDetail, UpsertOutcome, SensorReading, NewSensorReading, IngestionRun, NewIngestionRun,
+    HourlyPrecipitation, DailyWeather, KbdiDay, NoonObservation, FwiDay, NfdrsDay, NewRiskGridCell,
+    PARAM_AQI, PARAM_HUMIDITY, PARAM_PRECIPITATION, PARAM_TEMPERATURE, PARAM_WIND_SPEED,
+};
+
+// Postgres caps a statement at 65535 bind parameters; 7 per reading row
+const READING_INSERT_CHUNK: usize = 1000;
+// 11 per risk grid cell
+const RISK_CELL_UPSERT_CHUNK: usize = 5000;
+
+// Tiles are 4096 units square with a 64 unit buffer so features crossing an edge join up
+const TILE_EXTENT: i32 = 4096;
+const TILE_BUFFER: i32 = 64;
 
 pub struct Database {
     pool: Pool<Postgres>,
@@ -31,6 +45,7 @@ impl Database {
                 location, 
                 ST_MakeEnvelope($1, $2, $3, $4, 4326)
             )
+              AND decommissioned_at IS NULL
             ORDER BY name
             "#
         )
@@ -41,21 +56,21 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let sensors: Vec<Sensor> = rows.into_iter().map(|row| {
-            Sensor {
-                id: row.get("id"),
-                name: row.get("name"),
-                data_source: row.get("data_source"),
-                location: row.get("location"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }
-        }).collect();
-
-        Ok(sensors.into_iter().map(SensorLocation::from).collect())
+        let sensors = rows.iter().map(sensor_from_row).map(SensorLocation::from).collect();
+        self.with_latest_readings(sensors).await
     }
 
     pub async fn get_all_sensors(&self) -> Result<Vec<SensorLocation>, SqlxError> {
+        self.list_sensors(false).await
+    }
+
+    /// Sensors still reporting upstream, which the ingestion worker polls
+    pub async fn get_active_sensors(&self) -> Result<Vec<SensorLocation>, SqlxError> {
+        self.list_sensors(true).await
+    }
+
+    /// Sensors that are not decommissioned, by name
+    async fn list_sensors(&self, active_only: bool) -> Result<Vec<SensorLocation>, SqlxError> {
         let rows = sqlx::query(
             r#"
             SELECT 
@@ -66,44 +81,208 @@ impl Database {
                 created_at,
                 updated_at
             FROM sensors 
+            WHERE decommissioned_at IS NULL
+              AND (active OR NOT $1)
             ORDER BY name
             "#
         )
+        .bind(active_only)
         .fetch_all(&self.pool)
         .await?;
 
-        let sensors: Vec<Sensor> = rows.into_iter().map(|row| {
-            Sensor {
-                id: row.get("id"),
-                name: row.get("name"),
-                data_source: row.get("data_source"),
-                location: row.get("location"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }
-        }).collect();
-
-        Ok(sensors.into_iter().map(SensorLocation::from).collect())
+        let sensors = rows.iter().map(sensor_from_row).map(SensorLocation::from).collect();
+        self.with_latest_readings(sensors).await
     }
 
     pub async fn insert_sensor(&self, sensor: NewSensor) -> Result<i32, SqlxError> {
+        Self::insert_sensor_on(&mut *self.pool.acquire().await?, &sensor).await
+    }
+
+    async fn insert_sensor_on(conn: &mut PgConnection, sensor: &NewSensor) -> Result<i32, SqlxError> {
         let record = sqlx::query!(
             r#"
-            INSERT INTO sensors (name, data_source, location)
-            VALUES ($1, $2, ST_SetSRID(ST_MakePoint($3, $4), 4326))
+            INSERT INTO sensors (name, data_source, location, external_id, elevation, agency, parameters, active)
+            VALUES ($1, $2, ST_SetSRID(ST_MakePoint($3, $4), 4326), $5, $6, $7, $8, $9)
             RETURNING id
             "#,
             sensor.name,
             sensor.data_source,
             sensor.longitude,
-            sensor.latitude
+            sensor.latitude,
+            sensor.external_id,
+            sensor.elevation,
+            sensor.agency,
+            &sensor.parameters,
+            sensor.active
         )
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
         .await?;
 
         Ok(record.id)
     }
 
+    /// Insert a sensor, or update the one with the same `external_id` in its data source.
+    /// Unchanged and decommissioned sensors are not written, so `updated_at` only moves on
+    /// real changes. Sensors without an external id are always inserted.
+    pub async fn upsert_sensor(&self, sensor: &NewSensor) -> Result<(i32, UpsertOutcome), SqlxError> {
+        Self::upsert_sensor_on(&mut *self.pool.acquire().await?, sensor).await
+    }
+
+    /// `upsert_sensor` on one connection, so importers can run it inside their own transaction
+    pub async fn upsert_sensor_on(conn: &mut PgConnection, sensor: &NewSensor) -> Result<(i32, UpsertOutcome), SqlxError> {
+        let Some(external_id) = sensor.external_id.as_deref() else {
+            return Ok((Self::insert_sensor_on(conn, sensor).await?, UpsertOutcome::Inserted));
+        };
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO sensors (name, data_source, location, external_id, elevation, agency, parameters, active)
+            VALUES ($1, $2, ST_SetSRID(ST_MakePoint($3, $4), 4326), $5, $6, $7, $8, $9)
+            ON CONFLICT (data_source, external_id) DO UPDATE
+            SET name = EXCLUDED.name,
+                location = EXCLUDED.location,
+                elevation = EXCLUDED.elevation,
+                agency = EXCLUDED.agency,
+                parameters = EXCLUDED.parameters,
+                active = EXCLUDED.active
+            WHERE sensors.decommissioned_at IS NULL
+              AND (
+                (sensors.name, sensors.elevation, sensors.agency, sensors.parameters, sensors.active)
+                    IS DISTINCT FROM
+                (EXCLUDED.name, EXCLUDED.elevation, EXCLUDED.agency, EXCLUDED.parameters, EXCLUDED.active)
+                OR NOT ST_Equals(sensors.location, EXCLUDED.location)
+              )
+            RETURNING id, (xmax = 0) AS inserted
+            "#
+        )
+        .bind(&sensor.name)
+        .bind(&sensor.data_source)
+        .bind(sensor.longitude)
+        .bind(sensor.latitude)
+        .bind(external_id)
+        .bind(sensor.elevation)
+        .bind(&sensor.agency)
+        .bind(&sensor.parameters)
+        .bind(sensor.active)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        if let Some(row) = row {
+            let outcome = if row.get("inserted") { UpsertOutcome::Inserted } else { UpsertOutcome::Updated };
+            return Ok((row.get("id"), outcome));
+        }
+
+        // The conflicting row was left as it was
+        let row = sqlx::query(
+            "SELECT id, decommissioned_at IS NOT NULL AS decommissioned FROM sensors WHERE data_source = $1 AND external_id = $2"
+        )
+        .bind(&sensor.data_source)
+        .bind(external_id)
+        .fetch_one(conn)
+        .await?;
+        let outcome = if row.get("decommissioned") { UpsertOutcome::Decommissioned } else { UpsertOutcome::Unchanged };
+        Ok((row.get("id"), outcome))
+    }
+
+    /// Every sensor ever registered, decommissioned ones included
+    pub async fn get_sensor_count(&self) -> Result<i64, SqlxError> {
+        let record = sqlx::query!("SELECT COUNT(*) as count FROM sensors")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(record.count.unwrap_or(0))
+    }
+
+    /// One sensor by id, including a decommissioned one
+    pub async fn get_sensor(&self, sensor_id: i32) -> Result<Option<SensorDetail>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                id,
+                name,
+                data_source,
+                ST_Y(location) AS latitude,
+                ST_X(location) AS longitude,
+                external_id,
+                elevation,
+                agency,
+                parameters,
+                active,
+                created_at,
+                updated_at,
+                decommissioned_at
+            FROM sensors
+            WHERE id = $1
+            "#
+        )
+        .bind(sensor_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| SensorDetail {
+            id: row.get("id"),
+            name: row.get("name"),
+            data_source: row.get("data_source"),
+            latitude: row.get("latitude"),
+            longitude: row.get("longitude"),
+            external_id: row.get("external_id"),
+            elevation: row.get("elevation"),
+            agency: row.get("agency"),
+            parameters: row.get("parameters"),
+            active: row.get("active"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            decommissioned_at: row.get("decommissioned_at"),
+        }))
+    }
+
+    /// Replace an active sensor's fields. Returns false if there is no such active sensor.
+    /// `updated_at` is maintained by a trigger.
+    pub async fn update_sensor(&self, sensor_id: i32, sensor: &NewSensor) -> Result<bool, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sensors
+            SET name = $2,
+                data_source = $3,
+                location = ST_SetSRID(ST_MakePoint($4, $5), 4326),
+                external_id = $6,
+                elevation = $7,
+                agency = $8,
+                parameters = $9,
+                active = $10
+            WHERE id = $1
+              AND decommissioned_at IS NULL
+            "#
+        )
+        .bind(sensor_id)
+        .bind(&sensor.name)
+        .bind(&sensor.data_source)
+        .bind(sensor.longitude)
+        .bind(sensor.latitude)
+        .bind(&sensor.external_id)
+        .bind(sensor.elevation)
+        .bind(&sensor.agency)
+        .bind(&sensor.parameters)
+        .bind(sensor.active)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stop listing and polling a sensor while keeping its history. Returns false if it was
+    /// not active.
+    pub async fn decommission_sensor(&self, sensor_id: i32) -> Result<bool, SqlxError> {
+        let result = sqlx::query(
+            "UPDATE sensors SET decommissioned_at = NOW() WHERE id = $1 AND decommissioned_at IS NULL"
+        )
+        .bind(sensor_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn get_sensors_near_point(
         &self,
         latitude: f64,
@@ -123,8 +302,9 @@ impl Database {
             WHERE ST_DWithin(
                 location::geography, 
                 ST_SetSRID(ST_MakePoint($1, $2), 4326)::geography,
-                $3 * 1000
+                $3
             )
+              AND decommissioned_at IS NULL
             ORDER BY ST_Distance(
                 location::geography, 
                 ST_SetSRID(ST_MakePoint($1, $2), 4326)::geography
@@ -137,17 +317,880 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let sensors: Vec<Sensor> = rows.into_iter().map(|row| {
-            Sensor {
+        let sensors = rows.iter().map(sensor_from_row).map(SensorLocation::from).collect();
+        self.with_latest_readings(sensors).await
+    }
+
+    /// Insert a batch of readings, skipping observations that are already stored.
+    /// Returns the number of rows actually inserted.
+    pub async fn insert_readings(&self, readings: &[NewSensorReading]) -> Result<u64, SqlxError> {
+        let mut inserted = 0;
+
+        for chunk in readings.chunks(READING_INSERT_CHUNK) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO sensor_readings (sensor_id, parameter, value, unit, aqi, observed_at, source) "
+            );
+            builder.push_values(chunk, |mut row, reading| {
+                row.push_bind(reading.sensor_id)
+                    .push_bind(&reading.parameter)
+                    .push_bind(reading.value)
+                    .push_bind(&reading.unit)
+                    .push_bind(reading.aqi)
+                    .push_bind(reading.observed_at)
+                    .push_bind(&reading.source);
+            });
+            builder.push(" ON CONFLICT (sensor_id, parameter, source, observed_at) DO NOTHING");
+
+            inserted += builder.build().execute(&self.pool).await?.rows_affected();
+        }
+
+        Ok(inserted)
+    }
+
+    pub async fn sensor_exists(&self, sensor_id: i32) -> Result<bool, SqlxError> {
+        let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM sensors WHERE id = $1) AS found")
+            .bind(sensor_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get("found"))
+    }
+
+    /// Readings for one sensor observed within `[start, end]`, oldest first.
+    pub async fn get_sensor_readings(
+        &self,
+        sensor_id: i32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        parameter: Option<&str>,
+    ) -> Result<Vec<SensorReading>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                id,
+                sensor_id,
+                parameter,
+                value,
+                unit,
+                aqi,
+                observed_at,
+                source
+            FROM sensor_readings 
+            WHERE sensor_id = $1
+              AND observed_at BETWEEN $2 AND $3
+              AND ($4::VARCHAR IS NULL OR parameter = $4)
+            ORDER BY observed_at, parameter
+            "#
+        )
+        .bind(sensor_id)
+        .bind(start)
+        .bind(end)
+        .bind(parameter)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(reading_from_row).collect())
+    }
+
+    /// Hourly precipitation (mm) for the given sensors since `since`, oldest first.
+    /// Each reading covers the hour before it, and a sensor is polled several times
+    /// an hour, so the largest reading in each clock hour stands for that hour.
+    pub async fn get_hourly_precipitation(
+        &self,
+        sensor_ids: &[i32],
+        parameter: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<HourlyPrecipitation>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                sensor_id,
+                date_trunc('hour', observed_at) AS hour,
+                MAX(value) AS millimeters
+            FROM sensor_readings 
+            WHERE sensor_id = ANY($1)
+              AND parameter = $2
+              AND unit = 'mm'
+              AND value IS NOT NULL
+              AND observed_at >= $3
+            GROUP BY sensor_id, date_trunc('hour', observed_at)
+            ORDER BY sensor_id, hour
+            "#
+        )
+        .bind(sensor_ids)
+        .bind(parameter)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| HourlyPrecipitation {
+                sensor_id: row.get("sensor_id"),
+                hour: row.get("hour"),
+                millimeters: row.get("millimeters"),
+            })
+            .collect())
+    }
+
+    /// Daily temperature range (°C), humidity range (%), rainfall (mm) and hours with rain for
+    /// one sensor over `[start, end]`, by UTC day. Only days with at least one observation are returned.
+    pub async fn get_daily_weather(
+        &self,
+        sensor_id: i32,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DailyWeather>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            WITH readings AS (
+                SELECT parameter, value, observed_at, (observed_at AT TIME ZONE 'UTC')::DATE AS day
+                FROM sensor_readings
+                WHERE sensor_id = $1
+                  AND value IS NOT NULL
+                  AND observed_at >= ($2::DATE)::TIMESTAMP AT TIME ZONE 'UTC'
+                  AND observed_at < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE 'UTC'
+            ),
+            ranges AS (
+                SELECT
+                    day,
+                    MIN(value) FILTER (WHERE parameter = $4) AS min_temperature,
+                    MAX(value) FILTER (WHERE parameter = $4) AS max_temperature,
+                    MIN(value) FILTER (WHERE parameter = $6) AS min_humidity,
+                    MAX(value) FILTER (WHERE parameter = $6) AS max_humidity
+                FROM readings
+                WHERE parameter IN ($4, $6)
+                GROUP BY day
+            ),
+            hourly_rain AS (
+                SELECT day, date_trunc('hour', observed_at AT TIME ZONE 'UTC') AS hour, MAX(value) AS millimeters
+                FROM readings
+                WHERE parameter = $5
+                GROUP BY day, date_trunc('hour', observed_at AT TIME ZONE 'UTC')
+            ),
+            rain AS (
+                SELECT day, SUM(millimeters) AS rainfall, COUNT(*) FILTER (WHERE millimeters > 0) AS wet_hours
+                FROM hourly_rain
+                GROUP BY day
+            )
+            SELECT 
+                COALESCE(t.day, r.day) AS day,
+                t.min_temperature,
+                t.max_temperature,
+                t.min_humidity,
+                t.max_humidity,
+                r.rainfall,
+                COALESCE(r.wet_hours, 0)::INTEGER AS wet_hours
+            FROM ranges t
+            FULL OUTER JOIN rain r ON r.day = t.day
+            ORDER BY day
+            "#
+        )
+        .bind(sensor_id)
+        .bind(start)
+        .bind(end)
+        .bind(PARAM_TEMPERATURE)
+        .bind(PARAM_PRECIPITATION)
+        .bind(PARAM_HUMIDITY)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| DailyWeather {
+                date: row.get("day"),
+                min_temperature_c: row.get("min_temperature"),
+                max_temperature_c: row.get("max_temperature"),
+                min_humidity: row.get("min_humidity"),
+                max_humidity: row.get("max_humidity"),
+                rainfall_mm: row.get("rainfall"),
+                wet_hours: row.get("wet_hours"),
+            })
+            .collect())
+    }
+
+    /// UTC date of the oldest stored reading for a sensor
+    pub async fn get_first_observation_date(&self, sensor_id: i32) -> Result<Option<NaiveDate>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT (MIN(observed_at) AT TIME ZONE 'UTC')::DATE AS first_day
+            FROM sensor_readings 
+            WHERE sensor_id = $1
+            "#
+        )
+        .bind(sensor_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("first_day"))
+    }
+
+    /// The most recent KBDI row for each of the given sensors
+    pub async fn get_latest_kbdi(&self, sensor_ids: &[i32]) -> Result<Vec<KbdiDay>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (sensor_id)
+                sensor_id,
+                date,
+                kbdi,
+                spell_rainfall,
+                max_temperature,
+                rainfall
+            FROM kbdi_daily 
+            WHERE sensor_id = ANY($1)
+            ORDER BY sensor_id, date DESC
+            "#
+        )
+        .bind(sensor_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| KbdiDay {
+                sensor_id: row.get("sensor_id"),
+                date: row.get("date"),
+                kbdi: row.get("kbdi"),
+                spell_rainfall: row.get("spell_rainfall"),
+                max_temperature: row.get("max_temperature"),
+                rainfall: row.get("rainfall"),
+            })
+            .collect())
+    }
+
+    pub async fn upsert_kbdi_days(&self, days: &[KbdiDay]) -> Result<u64, SqlxError> {
+        if days.is_empty() {
+            return Ok(0);
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "INSERT INTO kbdi_daily (sensor_id, date, kbdi, spell_rainfall, max_temperature, rainfall) ",
+        );
+        builder.push_values(days, |mut row, day| {
+            row.push_bind(day.sensor_id)
+                .push_bind(day.date)
+                .push_bind(day.kbdi)
+                .push_bind(day.spell_rainfall)
+                .push_bind(day.max_temperature)
+                .push_bind(day.rainfall);
+        });
+        builder.push(
+            " ON CONFLICT (sensor_id, date) DO UPDATE SET \
+             kbdi = EXCLUDED.kbdi, spell_rainfall = EXCLUDED.spell_rainfall, \
+             max_temperature = EXCLUDED.max_temperature, rainfall = EXCLUDED.rainfall",
+        );
+
+        Ok(builder.build().execute(&self.pool).await?.rows_affected())
+    }
+
+    /// Weather at local noon for one sensor on each day in `[start, end]`: the reading of each
+    /// parameter closest to noon within two hours, and the rain (mm) in the 24 hours up to noon.
+    /// `noon_offset_seconds` is noon's offset from UTC midnight at the sensor.
+    pub async fn get_noon_weather(
+        &self,
+        sensor_id: i32,
+        start: NaiveDate,
+        end: NaiveDate,
+        noon_offset_seconds: i32,
+    ) -> Result<Vec<NoonObservation>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            WITH days AS (
+                SELECT
+                    d::DATE AS day,
+                    d::DATE::TIMESTAMP AT TIME ZONE 'UTC' + $4 * INTERVAL '1 second' AS noon
+                FROM generate_series($2::DATE, $3::DATE, INTERVAL '1 day') AS d
+            ),
+            noon_readings AS (
+                SELECT DISTINCT ON (days.day, r.parameter)
+                    days.day,
+                    r.parameter,
+                    r.value
+                FROM days
+                JOIN sensor_readings r
+                  ON r.sensor_id = $1
+                 AND r.parameter IN ($5, $6, $7)
+                 AND r.value IS NOT NULL
+                 AND r.observed_at BETWEEN days.noon - INTERVAL '2 hours' AND days.noon + INTERVAL '2 hours'
+                ORDER BY days.day, r.parameter, ABS(EXTRACT(EPOCH FROM r.observed_at - days.noon))
+            ),
+            hourly_rain AS (
+                SELECT days.day, date_trunc('hour', r.observed_at) AS hour, MAX(r.value) AS millimeters
+                FROM days
+                JOIN sensor_readings r
+                  ON r.sensor_id = $1
+                 AND r.parameter = $8
+                 AND r.value IS NOT NULL
+                 AND r.observed_at > days.noon - INTERVAL '24 hours'
+                 AND r.observed_at <= days.noon
+                GROUP BY days.day, date_trunc('hour', r.observed_at)
+            )
+            SELECT
+                days.day,
+                (SELECT value FROM noon_readings n WHERE n.day = days.day AND n.parameter = $5) AS temperature,
+                (SELECT value FROM noon_readings n WHERE n.day = days.day AND n.parameter = $6) AS humidity,
+                (SELECT value FROM noon_readings n WHERE n.day = days.day AND n.parameter = $7) AS wind_speed,
+                (SELECT SUM(millimeters) FROM hourly_rain h WHERE h.day = days.day) AS rainfall
+            FROM days
+            ORDER BY days.day
+            "#
+        )
+        .bind(sensor_id)
+        .bind(start)
+        .bind(end)
+        .bind(noon_offset_seconds)
+        .bind(PARAM_TEMPERATURE)
+        .bind(PARAM_HUMIDITY)
+        .bind(PARAM_WIND_SPEED)
+        .bind(PARAM_PRECIPITATION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| NoonObservation {
+                date: row.get("day"),
+                temperature_c: row.get("temperature"),
+                humidity: row.get("humidity"),
+                wind_speed_ms: row.get("wind_speed"),
+                rainfall_mm: row.get("rainfall"),
+            })
+            .collect())
+    }
+
+    /// Fire Weather Index rows for the given sensors since `since`, newest first per sensor
+    pub async fn get_fwi_days(&self, sensor_ids: &[i32], since: NaiveDate) -> Result<Vec<FwiDay>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                sensor_id,
+                date,
+                ffmc,
+                dmc,
+                dc,
+                isi,
+                bui,
+                fwi,
+                temperature,
+                humidity,
+                wind_speed,
+                rainfall
+            FROM fwi_daily 
+            WHERE sensor_id = ANY($1)
+              AND date >= $2
+            ORDER BY sensor_id, date DESC
+            "#
+        )
+        .bind(sensor_ids)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(fwi_day_from_row).collect())
+    }
+
+    /// The most recent Fire Weather Index row for a sensor
+    pub async fn get_latest_fwi(&self, sensor_id: i32) -> Result<Option<FwiDay>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT 
+                sensor_id,
+                date,
+                ffmc,
+                dmc,
+                dc,
+                isi,
+                bui,
+                fwi,
+                temperature,
+                humidity,
+                wind_speed,
+                rainfall
+            FROM fwi_daily 
+            WHERE sensor_id = $1
+            ORDER BY date DESC
+            LIMIT 1
+            "#
+        )
+        .bind(sensor_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(fwi_day_from_row))
+    }
+
+    pub async fn upsert_fwi_days(&self, days: &[FwiDay]) -> Result<u64, SqlxError> {
+        if days.is_empty() {
+            return Ok(0);
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "INSERT INTO fwi_daily (sensor_id, date, ffmc, dmc, dc, isi, bui, fwi, temperature, humidity, wind_speed, rainfall) ",
+        );
+        builder.push_values(days, |mut row, day| {
+            row.push_bind(day.sensor_id)
+                .push_bind(day.date)
+                .push_bind(day.ffmc)
+                .push_bind(day.dmc)
+                .push_bind(day.dc)
+                .push_bind(day.isi)
+                .push_bind(day.bui)
+                .push_bind(day.fwi)
+                .push_bind(day.temperature)
+                .push_bind(day.humidity)
+                .push_bind(day.wind_speed)
+                .push_bind(day.rainfall);
+        });
+        builder.push(
+            " ON CONFLICT (sensor_id, date) DO UPDATE SET \
+             ffmc = EXCLUDED.ffmc, dmc = EXCLUDED.dmc, dc = EXCLUDED.dc, \
+             isi = EXCLUDED.isi, bui = EXCLUDED.bui, fwi = EXCLUDED.fwi, \
+             temperature = EXCLUDED.temperature, humidity = EXCLUDED.humidity, \
+             wind_speed = EXCLUDED.wind_speed, rainfall = EXCLUDED.rainfall",
+        );
+
+        Ok(builder.build().execute(&self.pool).await?.rows_affected())
+    }
+
+    /// A sensor's most recent NFDRS fuel moisture rows, newest first
+    pub async fn get_recent_nfdrs_days(&self, sensor_id: i32, limit: i64) -> Result<Vec<NfdrsDay>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                sensor_id,
+                date,
+                hundred_hour,
+                thousand_hour,
+                thousand_hour_boundary
+            FROM nfdrs_daily 
+            WHERE sensor_id = $1
+            ORDER BY date DESC
+            LIMIT $2
+            "#
+        )
+        .bind(sensor_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(nfdrs_day_from_row).collect())
+    }
+
+    /// The most recent NFDRS fuel moisture row for each of the given sensors
+    pub async fn get_latest_nfdrs(&self, sensor_ids: &[i32]) -> Result<Vec<NfdrsDay>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (sensor_id)
+                sensor_id,
+                date,
+                hundred_hour,
+                thousand_hour,
+                thousand_hour_boundary
+            FROM nfdrs_daily 
+            WHERE sensor_id = ANY($1)
+            ORDER BY sensor_id, date DESC
+            "#
+        )
+        .bind(sensor_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(nfdrs_day_from_row).collect())
+    }
+
+    pub async fn upsert_nfdrs_days(&self, days: &[NfdrsDay]) -> Result<u64, SqlxError> {
+        if days.is_empty() {
+            return Ok(0);
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "INSERT INTO nfdrs_daily (sensor_id, date, hundred_hour, thousand_hour, thousand_hour_boundary) ",
+        );
+        builder.push_values(days, |mut row, day| {
+            row.push_bind(day.sensor_id)
+                .push_bind(day.date)
+                .push_bind(day.hundred_hour)
+                .push_bind(day.thousand_hour)
+                .push_bind(day.thousand_hour_boundary);
+        });
+        builder.push(
+            " ON CONFLICT (sensor_id, date) DO UPDATE SET \
+             hundred_hour = EXCLUDED.hundred_hour, thousand_hour = EXCLUDED.thousand_hour, \
+             thousand_hour_boundary = EXCLUDED.thousand_hour_boundary",
+        );
+
+        Ok(builder.build().execute(&self.pool).await?.rows_affected())
+    }
+
+    /// The most recent reading of every parameter for each of the given sensors.
+    pub async fn get_latest_readings(&self, sensor_ids: &[i32]) -> Result<Vec<SensorReading>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (sensor_id, parameter)
+                id,
+                sensor_id,
+                parameter,
+                value,
+                unit,
+                aqi,
+                observed_at,
+                source
+            FROM sensor_readings 
+            WHERE sensor_id = ANY($1)
+            ORDER BY sensor_id, parameter, observed_at DESC
+            "#
+        )
+        .bind(sensor_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(reading_from_row).collect())
+    }
+
+    /// Store scored grid cells, replacing earlier scores for the same cells
+    pub async fn upsert_risk_grid_cells(&self, cells: &[NewRiskGridCell]) -> Result<u64, SqlxError> {
+        // Cells are keyed on their corner, so round away float noise from the grid arithmetic
+        let key = |degrees: f64| (degrees * 1e6).round() / 1e6;
+
+        let mut upserted = 0;
+        for chunk in cells.chunks(RISK_CELL_UPSERT_CHUNK) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO risk_grid_cells (resolution, south, west, cell, probability, level, model_version, computed_at) ",
+            );
+            builder.push_values(chunk, |mut row, cell| {
+                let (south, west) = (key(cell.south), key(cell.west));
+                row.push_bind(cell.resolution)
+                    .push_bind(south)
+                    .push_bind(west)
+                    .push("ST_MakeEnvelope(")
+                    .push_bind_unseparated(west)
+                    .push_unseparated(", ")
+                    .push_bind_unseparated(south)
+                    .push_unseparated(", ")
+                    .push_bind_unseparated(west + cell.resolution)
+                    .push_unseparated(", ")
+                    .push_bind_unseparated(south + cell.resolution)
+                    .push_unseparated(", 4326)")
+                    .push_bind(cell.probability)
+                    .push_bind(&cell.level)
+                    .push_bind(&cell.model_version)
+                    .push_bind(cell.computed_at);
+            });
+            builder.push(
+                " ON CONFLICT (resolution, south, west) DO UPDATE SET \
+                 probability = EXCLUDED.probability, level = EXCLUDED.level, \
+                 model_version = EXCLUDED.model_version, computed_at = EXCLUDED.computed_at",
+            );
+            upserted += builder.build().execute(&self.pool).await?.rows_affected();
+        }
+        Ok(upserted)
+    }
+
+    /// Drop risk grid cells scored before `before`, which a later pass did not rescore
+    pub async fn delete_risk_grid_cells_before(&self, before: DateTime<Utc>) -> Result<u64, SqlxError> {
+        let result = sqlx::query("DELETE FROM risk_grid_cells WHERE computed_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Sensor locations in one web mercator tile, encoded as a Mapbox Vector Tile
+    pub async fn get_sensors_tile(&self, z: u32, x: u32, y: u32) -> Result<Vec<u8>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            WITH bounds AS (
+                SELECT ST_TileEnvelope($1, $2, $3) AS tile
+            ),
+            features AS (
+                SELECT
+                    ST_AsMVTGeom(ST_Transform(s.location, 3857), bounds.tile, $4, $5, true) AS geom,
+                    s.id,
+                    s.name,
+                    s.data_source
+                FROM sensors s, bounds
+                WHERE ST_Intersects(s.location, ST_Transform(bounds.tile, 4326))
+                  AND s.decommissioned_at IS NULL
+            )
+            SELECT COALESCE(ST_AsMVT(features.*, 'sensors', $4, 'geom'), ''::bytea) AS tile
+            FROM features
+            WHERE geom IS NOT NULL
+            "#
+        )
+        .bind(z as i32)
+        .bind(x as i32)
+        .bind(y as i32)
+        .bind(TILE_EXTENT)
+        .bind(TILE_BUFFER)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("tile"))
+    }
+
+    /// Sensors in one tile with their latest temperature (°C), humidity (%), wind speed (m/s)
+    /// and AQI observed since `since`. Sensors with nothing that recent are left out.
+    pub async fn get_readings_tile(&self, z: u32, x: u32, y: u32, since: DateTime<Utc>) -> Result<Vec<u8>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            WITH bounds AS (
+                SELECT ST_TileEnvelope($1, $2, $3) AS tile
+            ),
+            latest AS (
+                SELECT DISTINCT ON (r.sensor_id, r.parameter)
+                    r.sensor_id,
+                    r.parameter,
+                    r.value,
+                    r.unit,
+                    r.aqi,
+                    r.observed_at
+                FROM sensor_readings r
+                JOIN sensors s ON s.id = r.sensor_id
+                CROSS JOIN bounds
+                WHERE ST_Intersects(s.location, ST_Transform(bounds.tile, 4326))
+                  AND s.decommissioned_at IS NULL
+                  AND r.observed_at >= $6
+                ORDER BY r.sensor_id, r.parameter, r.observed_at DESC
+            ),
+            features AS (
+                SELECT
+                    ST_AsMVTGeom(ST_Transform(s.location, 3857), bounds.tile, $4, $5, true) AS geom,
+                    s.id,
+                    s.name,
+                    MAX(l.value) FILTER (WHERE l.parameter = $7 AND l.unit = 'C') AS temperature,
+                    MAX(l.value) FILTER (WHERE l.parameter = $8 AND l.unit = '%') AS humidity,
+                    MAX(l.value) FILTER (WHERE l.parameter = $9 AND l.unit = 'm/s') AS wind_speed,
+                    MAX(l.aqi) FILTER (WHERE l.parameter = $10) AS aqi,
+                    EXTRACT(EPOCH FROM MAX(l.observed_at))::bigint AS observed_at
+                FROM latest l
+                JOIN sensors s ON s.id = l.sensor_id
+                CROSS JOIN bounds
+                GROUP BY s.id, s.name, s.location, bounds.tile
+            )
+            SELECT COALESCE(ST_AsMVT(features.*, 'readings', $4, 'geom'), ''::bytea) AS tile
+            FROM features
+            WHERE geom IS NOT NULL
+            "#
+        )
+        .bind(z as i32)
+        .bind(x as i32)
+        .bind(y as i32)
+        .bind(TILE_EXTENT)
+        .bind(TILE_BUFFER)
+        .bind(since)
+        .bind(PARAM_TEMPERATURE)
+        .bind(PARAM_HUMIDITY)
+        .bind(PARAM_WIND_SPEED)
+        .bind(PARAM_AQI)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("tile"))
+    }
+
+    /// Risk grid cells in one tile scored since `since`. Finer cells are drawn over coarser ones.
+    pub async fn get_risk_tile(&self, z: u32, x: u32, y: u32, since: DateTime<Utc>) -> Result<Vec<u8>, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            WITH bounds AS (
+                SELECT ST_TileEnvelope($1, $2, $3) AS tile
+            ),
+            features AS (
+                SELECT
+                    ST_AsMVTGeom(ST_Transform(c.cell, 3857), bounds.tile, $4, $5, true) AS geom,
+                    c.probability,
+                    c.level,
+                    c.resolution,
+                    c.model_version,
+                    EXTRACT(EPOCH FROM c.computed_at)::bigint AS computed_at
+                FROM risk_grid_cells c, bounds
+                WHERE c.cell && ST_Transform(bounds.tile, 4326)
+                  AND c.computed_at >= $6
+                ORDER BY c.resolution DESC
+            )
+            SELECT COALESCE(ST_AsMVT(features.*, 'risk', $4, 'geom'), ''::bytea) AS tile
+            FROM features
+            WHERE geom IS NOT NULL
+            "#
+        )
+        .bind(z as i32)
+        .bind(x as i32)
+        .bind(y as i32)
+        .bind(TILE_EXTENT)
+        .bind(TILE_BUFFER)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("tile"))
+    }
+
+    pub async fn record_ingestion_run(&self, run: &NewIngestionRun) -> Result<i64, SqlxError> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO ingestion_runs (
+                started_at, finished_at, sensors_total, sensors_succeeded,
+                sensors_failed, readings_inserted, errors
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#
+        )
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.sensors_total)
+        .bind(run.sensors_succeeded)
+        .bind(run.sensors_failed)
+        .bind(run.readings_inserted)
+        .bind(&run.errors)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("id"))
+    }
+
+    pub async fn get_recent_ingestion_runs(&self, limit: i64) -> Result<Vec<IngestionRun>, SqlxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                id,
+                started_at,
+                finished_at,
+                sensors_total,
+                sensors_succeeded,
+                sensors_failed,
+                readings_inserted,
+                errors
+            FROM ingestion_runs 
+            ORDER BY started_at DESC
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            IngestionRun {
                 id: row.get("id"),
-                name: row.get("name"),
-                data_source: row.get("data_source"),
-                location: row.get("location"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
+                started_at: row.get("started_at"),
+                finished_at: row.get("finished_at"),
+                sensors_total: row.get("sensors_total"),
+                sensors_succeeded: row.get("sensors_succeeded"),
+                sensors_failed: row.get("sensors_failed"),
+                readings_inserted: row.get("readings_inserted"),
+                errors: row.get("errors"),
             }
-        }).collect();
+        }).collect())
+    }
+
+    async fn with_latest_readings(
+        &self,
+        mut sensors: Vec<SensorLocation>,
+    ) -> Result<Vec<SensorLocation>, SqlxError> {
+        if sensors.is_empty() {
+            return Ok(sensors);
+        }
+
+        let ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+        for reading in self.get_latest_readings(&ids).await? {
+            if let Some(sensor) = sensors.iter_mut().find(|s| s.id == reading.sensor_id) {
+                sensor.latest_readings.push(reading);
+            }
+        }
+
+        Ok(sensors)
+    }
+}
+
+fn sensor_from_row(row: &sqlx::postgres::PgRow) -> Sensor {
+    Sensor {
+        id: row.get("id"),
+        name: row.get("name"),
+        data_source: row.get("data_source"),
+        location: row.get("location"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn reading_from_row(row: &sqlx::postgres::PgRow) -> SensorReading {
+    SensorReading {
+        id: row.get("id"),
+        sensor_id: row.get("sensor_id"),
+        parameter: row.get("parameter"),
+        value: row.get("value"),
+        unit: row.get("unit"),
+        aqi: row.get("aqi"),
+        observed_at: row.get("observed_at"),
+        source: row.get("source"),
+    }
+}
+
+fn fwi_day_from_row(row: &sqlx::postgres::PgRow) -> FwiDay {
+    FwiDay {
+        sensor_id: row.get("sensor_id"),
+        date: row.get("date"),
+        ffmc: row.get("ffmc"),
+        dmc: row.get("dmc"),
+        dc: row.get("dc"),
+        isi: row.get("isi"),
+        bui: row.get("bui"),
+        fwi: row.get("fwi"),
+        temperature: row.get("temperature"),
+        humidity: row.get("humidity"),
+        wind_speed: row.get("wind_speed"),
+        rainfall: row.get("rainfall"),
+    }
+}
+
+fn nfdrs_day_from_row(row: &sqlx::postgres::PgRow) -> NfdrsDay {
+    NfdrsDay {
+        sensor_id: row.get("sensor_id"),
+        date: row.get("date"),
+        hundred_hour: row.get("hundred_hour"),
+        thousand_hour: row.get("thousand_hour"),
+        thousand_hour_boundary: row.get("thousand_hour_boundary"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Needs PostGIS with the migrations applied, e.g.
+    // TEST_DATABASE_URL=postgres://localhost/helios_test cargo test -- --ignored
+    #[tokio::test]
+    #[ignore = "needs a PostGIS database in TEST_DATABASE_URL"]
+    async fn test_sensors_near_point_stay_within_the_radius() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let db = Database::new(sqlx::PgPool::connect(&url).await.expect("Failed to connect"));
+        let data_source = format!("radius-test-{}", std::process::id());
+        let sensor = |name: &str, latitude: f64| NewSensor {
+            name: name.to_string(),
+            data_source: data_source.clone(),
+            latitude,
+            longitude: -121.3,
+            external_id: None,
+            elevation: None,
+            agency: None,
+            parameters: Vec::new(),
+            active: true,
+        };
+
+        // 0.9 degrees of latitude is about 100 km
+        let near = db.insert_sensor(sensor("Near", 44.06)).await.unwrap();
+        let far = db.insert_sensor(sensor("Far", 44.95)).await.unwrap();
+        let found = db.get_sensors_near_point(44.05, -121.3, 25.0).await;
+        sqlx::query("DELETE FROM sensors WHERE data_source = $1")
+            .bind(&data_source)
+            .execute(&db.pool)
+            .await
+            .unwrap();
 
-        Ok(sensors.into_iter().map(SensorLocation::from).collect())
+        let ids: Vec<i32> = found.unwrap().iter().map(|s| s.id).collect();
+        assert!(ids.contains(&near));
+        assert!(!ids.contains(&far), "a sensor 100 km away is not within 25 km");
     }
 }
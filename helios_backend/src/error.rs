@@ -0,0 +1,162 @@
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::clients::ProviderError;
+use crate::ml::ModelError;
+
+/// Every handler failure, mapped to an HTTP status and a stable error code
+#[derive(Debug)]
+pub enum ApiError {
+    UpstreamUnavailable(String), // AirNow, OpenWeatherMap, ...
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    Conflict(String), // e.g. a sensor name already taken within its data source
+    InputOutOfRange(String), // Conditions the risk model can't score
+    ModelFailure(String),
+    Database(String),
+}
+
+/// The `error` object in failed responses, and in `errors` for partial ones
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::UpstreamUnavailable(_) => StatusCode::BAD_GATEWAY,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::InputOutOfRange(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::ModelFailure(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::UpstreamUnavailable(_) => "UPSTREAM_UNAVAILABLE",
+            ApiError::BadRequest(_) => "BAD_REQUEST",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::Conflict(_) => "CONFLICT",
+            ApiError::InputOutOfRange(_) => "INPUT_OUT_OF_RANGE",
+            ApiError::ModelFailure(_) => "MODEL_FAILURE",
+            ApiError::Database(_) => "DATABASE_ERROR",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::UpstreamUnavailable(message)
+            | ApiError::BadRequest(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::NotFound(message)
+            | ApiError::Conflict(message)
+            | ApiError::InputOutOfRange(message)
+            | ApiError::ModelFailure(message)
+            | ApiError::Database(message) => message,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            code: self.code(),
+            message: self.message().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            eprintln!("Request failed: {}", self);
+        }
+
+        let body = json!({
+            "error": self.body(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        (status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiError::Database(err.to_string())
+    }
+}
+
+impl From<ProviderError> for ApiError {
+    fn from(err: ProviderError) -> Self {
+        match err {
+            ProviderError::NoData(message) => ApiError::NotFound(message),
+            other => ApiError::UpstreamUnavailable(other.to_string()),
+        }
+    }
+}
+
+impl From<ModelError> for ApiError {
+    fn from(err: ModelError) -> Self {
+        match err {
+            ModelError::UnknownVersion(_) => ApiError::NotFound(err.to_string()),
+            ModelError::OutOfRange(_) => ApiError::InputOutOfRange(err.to_string()),
+            ModelError::Load(_) | ModelError::Registry(_) | ModelError::Schema(_) => {
+                ApiError::ModelFailure(err.to_string())
+            }
+            _ => ApiError::ModelFailure(format!("Failed to make risk prediction: {}", err)),
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_response_has_status_and_code() {
+        let response = ApiError::NotFound("Sensor 7 not found".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+        assert_eq!(body["error"]["message"], "Sensor 7 not found");
+    }
+}
@@ -0,0 +1,175 @@
+// GeoJSON (RFC 7946) output. Endpoints that describe places can answer with GeoJSON instead
+// of their own JSON shape so GIS tools can load them directly. Clients ask for it with
+// `?format=geojson` or an `Accept: application/geo+json` header.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::models::SensorLocation;
+
+pub const CONTENT_TYPE: &str = "application/geo+json";
+
+/// The representation a client asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    #[serde(alias = "array")] // What /api/risk/grid called its plain JSON shape
+    Json,
+    GeoJson,
+}
+
+impl Format {
+    /// An explicit `format` query parameter wins over the Accept header
+    pub fn negotiate(requested: Option<Format>, headers: &HeaderMap) -> Format {
+        if let Some(format) = requested {
+            return format;
+        }
+        let accepts_geojson = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let media_type = parts.next().unwrap_or_default();
+                // q=0 means "not acceptable"
+                let refused = parts.any(|param| {
+                    param.strip_prefix("q=").and_then(|q| q.trim().parse::<f32>().ok()) == Some(0.0)
+                });
+                media_type.eq_ignore_ascii_case(CONTENT_TYPE) && !refused
+            });
+        if accepts_geojson {
+            Format::GeoJson
+        } else {
+            Format::Json
+        }
+    }
+}
+
+/// A response body in the negotiated format. Both carry `Vary: Accept` since either may be
+/// served from the same URL.
+#[derive(Debug)]
+pub struct Negotiated {
+    pub format: Format,
+    pub body: Value,
+}
+
+impl IntoResponse for Negotiated {
+    fn into_response(self) -> Response {
+        let mut response = Json(self.body).into_response();
+        let headers = response.headers_mut();
+        if self.format == Format::GeoJson {
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+        }
+        headers.insert(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+/// Positions are longitude first
+pub fn point(latitude: f64, longitude: f64) -> Value {
+    json!({
+        "type": "Point",
+        "coordinates": [longitude, latitude]
+    })
+}
+
+/// A box as a counterclockwise ring, per the RFC's right-hand rule
+pub fn bounds_polygon(south: f64, west: f64, north: f64, east: f64) -> Value {
+    json!({
+        "type": "Polygon",
+        "coordinates": [[[west, south], [east, south], [east, north], [west, north], [west, south]]]
+    })
+}
+
+pub fn feature(geometry: Value, properties: Value) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": properties
+    })
+}
+
+/// `members` are added alongside `features`, e.g. a timestamp; RFC 7946 calls these foreign members
+pub fn feature_collection(features: Vec<Value>, members: Value) -> Value {
+    let mut collection = json!({
+        "type": "FeatureCollection",
+        "features": features
+    });
+    if let (Some(collection), Value::Object(members)) = (collection.as_object_mut(), members) {
+        collection.extend(members);
+    }
+    collection
+}
+
+/// A response about a single location as a point feature. Its `location` member is dropped
+/// from the properties since the geometry carries it.
+pub fn point_feature(latitude: f64, longitude: f64, mut body: Value) -> Value {
+    if let Some(members) = body.as_object_mut() {
+        members.remove("location");
+    }
+    feature(point(latitude, longitude), body)
+}
+
+/// A sensor with its latest reading of each parameter, keyed by parameter
+pub fn sensor_feature(sensor: &SensorLocation) -> Value {
+    let latest_readings: serde_json::Map<String, Value> = sensor
+        .latest_readings
+        .iter()
+        .map(|reading| {
+            (
+                reading.parameter.clone(),
+                json!({
+                    "value": reading.value,
+                    "unit": reading.unit,
+                    "aqi": reading.aqi,
+                    "observed_at": reading.observed_at.to_rfc3339()
+                }),
+            )
+        })
+        .collect();
+
+    let mut feature = feature(
+        point(sensor.latitude, sensor.longitude),
+        json!({
+            "id": sensor.id,
+            "name": sensor.name,
+            "data_source": sensor.data_source,
+            "latest_readings": latest_readings
+        }),
+    );
+    feature["id"] = json!(sensor.id);
+    feature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_negotiation() {
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+            headers
+        };
+
+        assert_eq!(Format::negotiate(None, &HeaderMap::new()), Format::Json);
+        assert_eq!(Format::negotiate(None, &accept("application/geo+json")), Format::GeoJson);
+        assert_eq!(Format::negotiate(None, &accept("application/json, application/geo+json;q=0.9")), Format::GeoJson);
+        assert_eq!(Format::negotiate(None, &accept("application/geo+json; q=0")), Format::Json);
+        assert_eq!(Format::negotiate(None, &accept("*/*")), Format::Json);
+        assert_eq!(Format::negotiate(Some(Format::Json), &accept("application/geo+json")), Format::Json);
+        assert_eq!(Format::negotiate(Some(Format::GeoJson), &HeaderMap::new()), Format::GeoJson);
+
+        let feature = point_feature(44.1, -121.7, json!({ "location": {}, "risk": 0.4 }));
+        assert_eq!(feature["geometry"]["coordinates"], json!([-121.7, 44.1]));
+        assert!(feature["properties"].get("location").is_none());
+    }
+}
@@ -0,0 +1,234 @@
+// Regular latitude/longitude grids for painting risk over a region. Model features are only
+// known where there are observations, so each cell is filled by inverse distance weighting
+// (IDW) from the surrounding samples before the whole grid is scored in one batch.
+
+use serde::Serialize;
+
+use crate::ml::WeatherData;
+
+// Keeps one request to a single model batch of reasonable size
+pub const MAX_CELLS: usize = 10_000;
+pub const DEFAULT_RESOLUTION: f64 = 0.05; // Degrees, about 5 km
+pub const MIN_RESOLUTION: f64 = 0.001;
+
+// Samples this far outside the box still inform the cells along its edges
+pub const SAMPLE_MARGIN_DEGREES: f64 = 0.5;
+
+// Cell sizes the stored risk layer may use. Each divides a degree evenly, so a lattice block
+// starting on a whole degree puts every cell on the same global lattice.
+pub const LATTICE_RESOLUTIONS: [f64; 3] = [0.01, 0.05, 0.25];
+pub const LATTICE_BLOCK_DEGREES: f64 = 1.0;
+
+const IDW_POWER: f64 = 2.0;
+const KM_PER_DEGREE: f64 = 111.32;
+
+/// A grid of `rows` x `cols` square cells covering a bounding box. Row 0 is the southernmost
+/// row and column 0 the westernmost; cells are numbered row by row from the south-west corner.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GridSpec {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+    pub resolution: f64, // Cell size in degrees
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// Model features known at one location
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub features: WeatherData,
+}
+
+impl GridSpec {
+    /// The last row and column may extend past the box so that every point inside it is covered
+    pub fn new(min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64, resolution: f64) -> Result<Self, String> {
+        if min_lat >= max_lat || min_lon >= max_lon {
+            return Err("min_lat and min_lon must be less than max_lat and max_lon".to_string());
+        }
+        if !resolution.is_finite() || resolution < MIN_RESOLUTION {
+            return Err(format!("resolution must be at least {} degrees", MIN_RESOLUTION));
+        }
+
+        // Allow for rounding: (44.2 - 44.0) / 0.1 is slightly more than 2
+        let cells_across = |span: f64| ((span / resolution) - 1e-9).ceil().max(1.0) as usize;
+        let rows = cells_across(max_lat - min_lat);
+        let cols = cells_across(max_lon - min_lon);
+        if rows.saturating_mul(cols) > MAX_CELLS {
+            return Err(format!(
+                "A {} degree grid over these bounds has {} cells; the limit is {}. Use a coarser resolution",
+                resolution,
+                rows.saturating_mul(cols),
+                MAX_CELLS
+            ));
+        }
+
+        Ok(GridSpec {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+            resolution,
+            rows,
+            cols,
+        })
+    }
+
+    /// The whole-degree block containing a point, gridded at one of `LATTICE_RESOLUTIONS`
+    pub fn lattice_block(latitude: f64, longitude: f64, resolution: f64) -> Result<Self, String> {
+        if !LATTICE_RESOLUTIONS.contains(&resolution) {
+            return Err(format!("resolution must be one of {:?} degrees", LATTICE_RESOLUTIONS));
+        }
+        // Points on the north pole or antimeridian belong to the block below them
+        let block_origin = |value: f64, limit: f64| {
+            ((value / LATTICE_BLOCK_DEGREES).floor() * LATTICE_BLOCK_DEGREES).min(limit - LATTICE_BLOCK_DEGREES)
+        };
+        let south = block_origin(latitude, 90.0);
+        let west = block_origin(longitude, 180.0);
+        GridSpec::new(south, west, south + LATTICE_BLOCK_DEGREES, west + LATTICE_BLOCK_DEGREES, resolution)
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// (min_lat, min_lon, max_lat, max_lon) of one cell
+    pub fn cell_bounds(&self, row: usize, col: usize) -> (f64, f64, f64, f64) {
+        let south = self.min_lat + row as f64 * self.resolution;
+        let west = self.min_lon + col as f64 * self.resolution;
+        (south, west, south + self.resolution, west + self.resolution)
+    }
+
+    pub fn cell_center(&self, row: usize, col: usize) -> (f64, f64) {
+        let (south, west, north, east) = self.cell_bounds(row, col);
+        ((south + north) / 2.0, (west + east) / 2.0)
+    }
+
+    /// (row, col, center latitude, center longitude) for every cell, in cell order
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, f64, f64)> + '_ {
+        (0..self.rows).flat_map(move |row| {
+            (0..self.cols).map(move |col| {
+                let (lat, lon) = self.cell_center(row, col);
+                (row, col, lat, lon)
+            })
+        })
+    }
+
+    /// Where to look for samples: the grid plus a margin, clipped to valid coordinates
+    pub fn sample_bounds(&self) -> (f64, f64, f64, f64) {
+        (
+            (self.min_lat - SAMPLE_MARGIN_DEGREES).max(-90.0),
+            (self.min_lon - SAMPLE_MARGIN_DEGREES).max(-180.0),
+            (self.max_lat + SAMPLE_MARGIN_DEGREES).min(90.0),
+            (self.max_lon + SAMPLE_MARGIN_DEGREES).min(180.0),
+        )
+    }
+
+    /// Corners and center of the box, where forecast weather is sampled when no sensor has
+    /// fresh observations
+    pub fn anchor_points(&self) -> [(f64, f64); 5] {
+        [
+            (self.min_lat, self.min_lon),
+            (self.min_lat, self.max_lon),
+            (self.max_lat, self.min_lon),
+            (self.max_lat, self.max_lon),
+            ((self.min_lat + self.max_lat) / 2.0, (self.min_lon + self.max_lon) / 2.0),
+        ]
+    }
+}
+
+/// Inverse-distance weighted model features at a point. A sample at the point itself is
+/// used as is. `None` without samples.
+pub fn interpolate(samples: &[Sample], latitude: f64, longitude: f64) -> Option<WeatherData> {
+    let mut weighted = [0.0f64; 5];
+    let mut total_weight = 0.0;
+
+    for sample in samples {
+        let distance = distance_km(latitude, longitude, sample.latitude, sample.longitude);
+        if distance < 1e-6 {
+            return Some(sample.features.clone());
+        }
+        let weight = distance.powf(-IDW_POWER);
+        for (sum, value) in weighted.iter_mut().zip(sample.features.features()) {
+            *sum += weight * value as f64;
+        }
+        total_weight += weight;
+    }
+
+    if total_weight == 0.0 {
+        return None;
+    }
+    let [temperature, humidity, wind_speed, precipitation, drought_index] = weighted.map(|sum| (sum / total_weight) as f32);
+    Some(WeatherData {
+        temperature,
+        humidity,
+        wind_speed,
+        precipitation,
+        drought_index,
+    })
+}
+
+/// Equirectangular approximation; accurate to well under 1% over the distances IDW cares about
+fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let mean_lat = ((lat1 + lat2) / 2.0).to_radians();
+    let dx = (lon2 - lon1) * mean_lat.cos();
+    let dy = lat2 - lat1;
+    (dx * dx + dy * dy).sqrt() * KM_PER_DEGREE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latitude: f64, longitude: f64, temperature: f32) -> Sample {
+        Sample {
+            latitude,
+            longitude,
+            features: WeatherData {
+                temperature,
+                humidity: 30.0,
+                wind_speed: 10.0,
+                precipitation: 0.0,
+                drought_index: 40.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_grid_covers_the_box_and_interpolates_between_samples() {
+        let grid = GridSpec::new(44.0, -122.0, 44.25, -121.5, 0.1).unwrap();
+        assert_eq!((grid.rows, grid.cols), (3, 5));
+        assert_eq!(grid.cells().count(), 15);
+        let (lat, lon) = grid.cell_center(0, 0);
+        assert!((lat - 44.05).abs() < 1e-9 && (lon + 121.95).abs() < 1e-9);
+
+        let samples = [sample(44.0, -122.0, 60.0), sample(44.0, -121.0, 100.0)];
+        // Halfway between two samples is their mean; on top of one is that sample
+        let midpoint = interpolate(&samples, 44.0, -121.5).unwrap();
+        assert!((midpoint.temperature - 80.0).abs() < 1e-3, "got {}", midpoint.temperature);
+        assert_eq!(interpolate(&samples, 44.0, -121.0).unwrap().temperature, 100.0);
+        assert!(interpolate(&[], 44.0, -121.0).is_none());
+
+        assert!(GridSpec::new(44.0, -122.0, 45.0, -121.0, 0.001).is_err()); // 1,000,000 cells
+        assert!(GridSpec::new(45.0, -122.0, 44.0, -121.0, 0.1).is_err());
+    }
+
+    #[test]
+    fn test_lattice_blocks_share_one_global_lattice() {
+        // Nearby points in the same whole-degree block get the same cells
+        let block = GridSpec::lattice_block(44.13, -121.77, 0.05).unwrap();
+        assert_eq!(block, GridSpec::lattice_block(44.96, -121.01, 0.05).unwrap());
+        assert_eq!((block.min_lat, block.min_lon, block.rows, block.cols), (44.0, -122.0, 20, 20));
+
+        // Every corner is a multiple of the resolution
+        let (south, west, _, _) = block.cell_bounds(7, 13);
+        assert!(((south / 0.05) - (south / 0.05).round()).abs() < 1e-9);
+        assert!(((west / 0.05) - (west / 0.05).round()).abs() < 1e-9);
+
+        assert_eq!(GridSpec::lattice_block(89.5, 179.5, 0.25).unwrap().max_lat, 90.0);
+        assert!(GridSpec::lattice_block(44.13, -121.77, 0.1).is_err()); // Not a lattice resolution
+    }
+}
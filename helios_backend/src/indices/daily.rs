@@ -0,0 +1,443 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::IndicesConfig;
+use crate::database::Database;
+use crate::models::{FwiDay, KbdiDay, NfdrsDay, SensorLocation};
+use crate::units::{Precipitation, Speed, Temperature};
+
+use super::fwi::NoonWeather;
+use super::nfdrs::{self, DailyConditions};
+use super::{FwiComponents, KbdiState};
+
+// Days are only computed once they are over, so checking hourly is plenty
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+// How far back a sensor's first run reaches into already stored readings
+const MAX_BACKFILL_DAYS: i64 = 365;
+
+// Older state is not trusted for current conditions
+const MAX_STATE_AGE_DAYS: i64 = 7;
+
+/// Start the daily index job on the tokio runtime. The first pass runs immediately.
+pub fn spawn(db: Arc<Database>, config: IndicesConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let today = chrono::Utc::now().date_naive();
+            match update_all(&db, &config, today).await {
+                Ok(0) => {}
+                Ok(days) => println!("Daily indices: {} sensor-days updated", days),
+                Err(e) => eprintln!("Failed to update daily indices: {}", e),
+            }
+        }
+    })
+}
+
+/// Bring every sensor's daily indices up to date through yesterday (UTC).
+pub async fn update_all(db: &Database, config: &IndicesConfig, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let sensors = db.get_all_sensors().await?;
+
+    let mut updated = 0;
+    for sensor in &sensors {
+        match update_kbdi(db, config, sensor.id, today).await {
+            Ok(days) => updated += days,
+            Err(e) => eprintln!("Failed to update KBDI for sensor {} ({}): {}", sensor.id, sensor.name, e),
+        }
+        match update_fwi(db, sensor, today).await {
+            Ok(days) => updated += days,
+            Err(e) => eprintln!("Failed to update FWI for sensor {} ({}): {}", sensor.id, sensor.name, e),
+        }
+        match update_nfdrs(db, sensor, today).await {
+            Ok(days) => updated += days,
+            Err(e) => eprintln!("Failed to update NFDRS fuel moisture for sensor {} ({}): {}", sensor.id, sensor.name, e),
+        }
+    }
+    Ok(updated)
+}
+
+async fn update_kbdi(db: &Database, config: &IndicesConfig, sensor_id: i32, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let yesterday = today - chrono::Duration::days(1);
+
+    let latest = db.get_latest_kbdi(&[sensor_id]).await?.into_iter().next();
+    let (mut state, start) = match latest {
+        Some(day) => (
+            KbdiState { index: day.kbdi, spell_rainfall: day.spell_rainfall },
+            day.date + chrono::Duration::days(1),
+        ),
+        None => match db.get_first_observation_date(sensor_id).await? {
+            Some(first) => (
+                KbdiState::new(config.kbdi_initial),
+                first.max(yesterday - chrono::Duration::days(MAX_BACKFILL_DAYS)),
+            ),
+            None => return Ok(0), // Nothing observed yet
+        },
+    };
+    if start > yesterday {
+        return Ok(0);
+    }
+
+    let weather = db.get_daily_weather(sensor_id, start, yesterday).await?;
+    let days: Vec<KbdiDay> = start
+        .iter_days()
+        .take_while(|date| *date <= yesterday)
+        .map(|date| {
+            // A day without observations gets no drying and no rain
+            let observed = weather.iter().find(|w| w.date == date);
+            let max_temperature = observed.and_then(|w| w.max_temperature_c);
+            let rainfall = observed.and_then(|w| w.rainfall_mm);
+
+            state = state.next_day(
+                max_temperature.map(Temperature::from_celsius),
+                Precipitation::from_millimeters(rainfall.unwrap_or(0.0)),
+                config.mean_annual_rainfall,
+            );
+            KbdiDay {
+                sensor_id,
+                date,
+                kbdi: state.index,
+                spell_rainfall: state.spell_rainfall,
+                max_temperature,
+                rainfall,
+            }
+        })
+        .collect();
+
+    db.upsert_kbdi_days(&days).await
+}
+
+/// Noon at the sensor as an offset from UTC midnight. Local mean solar noon stands in for
+/// noon standard time, which is within about half an hour of it inside a time zone.
+fn noon_offset_seconds(longitude: f64) -> i32 {
+    (43_200.0 - longitude * 240.0).round() as i32
+}
+
+async fn update_fwi(db: &Database, sensor: &SensorLocation, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let yesterday = today - chrono::Duration::days(1);
+
+    let latest = db.get_latest_fwi(sensor.id).await?;
+    let start = match &latest {
+        Some(day) => day.date + chrono::Duration::days(1),
+        None => match db.get_first_observation_date(sensor.id).await? {
+            Some(first) => first.max(yesterday - chrono::Duration::days(MAX_BACKFILL_DAYS)),
+            None => return Ok(0),
+        },
+    };
+    if start > yesterday {
+        return Ok(0);
+    }
+
+    // The season starts from the standard start-up codes
+    let mut previous: Option<FwiComponents> = latest.as_ref().map(|day| FwiComponents {
+        ffmc: day.ffmc,
+        dmc: day.dmc,
+        dc: day.dc,
+        isi: day.isi,
+        bui: day.bui,
+        fwi: day.fwi,
+    });
+
+    let observations = db
+        .get_noon_weather(sensor.id, start, yesterday, noon_offset_seconds(sensor.longitude))
+        .await?;
+    let mut days = Vec::new();
+    for observation in observations {
+        let weather = match (observation.temperature_c, observation.humidity, observation.wind_speed_ms) {
+            (Some(t), Some(h), Some(w)) => Some(NoonWeather {
+                temperature: Temperature::from_celsius(t),
+                humidity: h,
+                wind_speed: Speed::from_meters_per_second(w),
+                rainfall: Precipitation::from_millimeters(observation.rainfall_mm.unwrap_or(0.0)),
+            }),
+            _ => None,
+        };
+
+        // Without noon weather the codes are carried forward unchanged
+        let components = match weather {
+            Some(weather) => previous
+                .map(|c| c.state())
+                .unwrap_or_default()
+                .next_day(&weather, observation.date.month()),
+            None => match previous {
+                Some(components) => components,
+                None => continue, // Nothing to carry before the first observed noon
+            },
+        };
+        previous = Some(components);
+
+        days.push(FwiDay {
+            sensor_id: sensor.id,
+            date: observation.date,
+            ffmc: components.ffmc,
+            dmc: components.dmc,
+            dc: components.dc,
+            isi: components.isi,
+            bui: components.bui,
+            fwi: components.fwi,
+            temperature: weather.map(|w| w.temperature.celsius()),
+            humidity: weather.map(|w| w.humidity),
+            wind_speed: weather.map(|w| w.wind_speed.kilometers_per_hour()),
+            rainfall: weather.map(|w| w.rainfall.millimeters()),
+        });
+    }
+
+    db.upsert_fwi_days(&days).await
+}
+
+/// Recent Fire Weather Index rows for the nearest sensor that has any, newest first.
+/// Sensors are expected in nearest-first order, as returned by `get_sensors_near_point`.
+pub async fn fwi_history(
+    db: &Database,
+    sensors: &[SensorLocation],
+    since: NaiveDate,
+) -> Result<Option<(i32, Vec<FwiDay>)>, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    if sensor_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let days = db.get_fwi_days(&sensor_ids, since).await?;
+    Ok(sensor_ids.into_iter().find_map(|sensor_id| {
+        let sensor_days: Vec<FwiDay> = days.iter().filter(|d| d.sensor_id == sensor_id).cloned().collect();
+        (!sensor_days.is_empty()).then_some((sensor_id, sensor_days))
+    }))
+}
+
+async fn update_nfdrs(db: &Database, sensor: &SensorLocation, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let yesterday = today - chrono::Duration::days(1);
+
+    // The last week of rows, oldest first, feeds the 1000-hour average
+    let mut window: VecDeque<NfdrsDay> = db
+        .get_recent_nfdrs_days(sensor.id, nfdrs::THOUSAND_HOUR_WINDOW as i64)
+        .await?
+        .into_iter()
+        .rev()
+        .collect();
+    let start = match window.back() {
+        Some(day) => day.date + chrono::Duration::days(1),
+        None => match db.get_first_observation_date(sensor.id).await? {
+            Some(first) => first.max(yesterday - chrono::Duration::days(MAX_BACKFILL_DAYS)),
+            None => return Ok(0),
+        },
+    };
+    if start > yesterday {
+        return Ok(0);
+    }
+
+    let weather = db.get_daily_weather(sensor.id, start, yesterday).await?;
+    let mut days = Vec::new();
+    for date in start.iter_days().take_while(|date| *date <= yesterday) {
+        let observed = weather.iter().find(|w| w.date == date);
+        let conditions = observed.and_then(|w| {
+            Some(DailyConditions {
+                min_temperature: Temperature::from_celsius(w.min_temperature_c?),
+                max_temperature: Temperature::from_celsius(w.max_temperature_c?),
+                min_humidity: w.min_humidity?,
+                max_humidity: w.max_humidity?,
+                wet_hours: w.wet_hours as f64,
+                latitude: sensor.latitude,
+                day_of_year: date.ordinal(),
+            })
+        });
+
+        let previous = window.back();
+        let (hundred_hour, boundary) = match (conditions, previous) {
+            (Some(conditions), _) => {
+                let (hundred_hour_boundary, thousand_hour_boundary) = nfdrs::daily_boundaries(&conditions);
+                let yesterday_100 = previous.map_or(nfdrs::HUNDRED_HOUR_STARTUP, |p| p.hundred_hour);
+                (nfdrs::next_hundred_hour(yesterday_100, hundred_hour_boundary), thousand_hour_boundary)
+            }
+            // Without temperature and humidity the moistures hold where they were
+            (None, Some(previous)) => (previous.hundred_hour, previous.thousand_hour_boundary),
+            (None, None) => continue,
+        };
+
+        // The 1000-hour moisture moves from its value a week ago towards the week's mean boundary
+        let week_ago = if window.len() == nfdrs::THOUSAND_HOUR_WINDOW {
+            window.front().map_or(nfdrs::THOUSAND_HOUR_STARTUP, |d| d.thousand_hour)
+        } else {
+            nfdrs::THOUSAND_HOUR_STARTUP
+        };
+        let recent_boundaries = window
+            .iter()
+            .skip(window.len().saturating_sub(nfdrs::THOUSAND_HOUR_WINDOW - 1))
+            .map(|d| d.thousand_hour_boundary);
+        let boundaries: Vec<f64> = recent_boundaries.chain(std::iter::once(boundary)).collect();
+        let mean_boundary = boundaries.iter().sum::<f64>() / boundaries.len() as f64;
+
+        let day = NfdrsDay {
+            sensor_id: sensor.id,
+            date,
+            hundred_hour,
+            thousand_hour: nfdrs::next_thousand_hour(week_ago, mean_boundary),
+            thousand_hour_boundary: boundary,
+        };
+        window.push_back(day.clone());
+        if window.len() > nfdrs::THOUSAND_HOUR_WINDOW {
+            window.pop_front();
+        }
+        days.push(day);
+    }
+
+    db.upsert_nfdrs_days(&days).await
+}
+
+/// Heavy dead fuel moistures used for a location's NFDRS indices
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentHeavyFuels {
+    pub hundred_hour: f64,
+    pub thousand_hour: f64,
+    pub date: Option<NaiveDate>, // Last day tracked
+    pub sensor_id: Option<i32>,
+    pub source: &'static str, // "tracked", or "startup" when no sensor nearby has recent values
+}
+
+/// Latest tracked 100- and 1000-hour moistures from the nearest sensor with recent ones,
+/// else the NFDRS start-up values. Sensors are expected in nearest-first order.
+pub async fn current_heavy_fuels(
+    db: &Database,
+    sensors: &[SensorLocation],
+    today: NaiveDate,
+) -> Result<CurrentHeavyFuels, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    let latest = if sensor_ids.is_empty() {
+        Vec::new()
+    } else {
+        db.get_latest_nfdrs(&sensor_ids).await?
+    };
+    let oldest_allowed = today - chrono::Duration::days(MAX_STATE_AGE_DAYS);
+
+    Ok(sensor_ids
+        .iter()
+        .find_map(|id| latest.iter().find(|day| day.sensor_id == *id && day.date >= oldest_allowed))
+        .map(|day| CurrentHeavyFuels {
+            hundred_hour: day.hundred_hour,
+            thousand_hour: day.thousand_hour,
+            date: Some(day.date),
+            sensor_id: Some(day.sensor_id),
+            source: "tracked",
+        })
+        .unwrap_or(CurrentHeavyFuels {
+            hundred_hour: nfdrs::HUNDRED_HOUR_STARTUP,
+            thousand_hour: nfdrs::THOUSAND_HOUR_STARTUP,
+            date: None,
+            sensor_id: None,
+            source: "startup",
+        }))
+}
+
+/// The KBDI used for a location, reported alongside risk scores
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentKbdi {
+    pub kbdi: f64,
+    pub date: NaiveDate, // Last day the index covers
+    pub sensor_id: Option<i32>,
+    pub source: &'static str, // "kbdi" or "default" when no sensor nearby has one
+    #[serde(skip)]
+    pub state: KbdiState,
+}
+
+/// Latest KBDI from the nearest sensor that has a recent one, else the configured initial value.
+/// Sensors are expected in nearest-first order, as returned by `get_sensors_near_point`.
+pub async fn current_kbdi(
+    db: &Database,
+    sensors: &[SensorLocation],
+    config: &IndicesConfig,
+    today: NaiveDate,
+) -> Result<CurrentKbdi, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    let latest = recent_kbdi(db, &sensor_ids, today).await?;
+
+    Ok(sensor_ids
+        .iter()
+        .find_map(|id| latest.iter().find(|day| day.sensor_id == *id))
+        .map(tracked_kbdi)
+        .unwrap_or_else(|| default_kbdi(config, today)))
+}
+
+/// Latest KBDI for each sensor, falling back to the configured initial value per sensor
+pub async fn current_kbdi_by_sensor(
+    db: &Database,
+    sensors: &[SensorLocation],
+    config: &IndicesConfig,
+    today: NaiveDate,
+) -> Result<HashMap<i32, CurrentKbdi>, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    let latest = recent_kbdi(db, &sensor_ids, today).await?;
+
+    Ok(sensor_ids
+        .into_iter()
+        .map(|id| {
+            let current = latest
+                .iter()
+                .find(|day| day.sensor_id == id)
+                .map(tracked_kbdi)
+                .unwrap_or_else(|| default_kbdi(config, today));
+            (id, current)
+        })
+        .collect())
+}
+
+async fn recent_kbdi(db: &Database, sensor_ids: &[i32], today: NaiveDate) -> Result<Vec<KbdiDay>, sqlx::Error> {
+    if sensor_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let oldest_allowed = today - chrono::Duration::days(MAX_STATE_AGE_DAYS);
+    let mut latest = db.get_latest_kbdi(sensor_ids).await?;
+    latest.retain(|day| day.date >= oldest_allowed);
+    Ok(latest)
+}
+
+fn tracked_kbdi(day: &KbdiDay) -> CurrentKbdi {
+    CurrentKbdi {
+        kbdi: day.kbdi,
+        date: day.date,
+        sensor_id: Some(day.sensor_id),
+        source: "kbdi",
+        state: KbdiState { index: day.kbdi, spell_rainfall: day.spell_rainfall },
+    }
+}
+
+fn default_kbdi(config: &IndicesConfig, today: NaiveDate) -> CurrentKbdi {
+    let state = KbdiState::new(config.kbdi_initial);
+    CurrentKbdi {
+        kbdi: state.index,
+        date: today - chrono::Duration::days(1),
+        sensor_id: None,
+        source: "default",
+        state,
+    }
+}
+
+/// Step the index forward through forecast days. The result maps each forecast day to the
+/// index at its start, i.e. after all earlier days have been applied.
+pub fn project_kbdi(
+    current: &CurrentKbdi,
+    forecast_days: &BTreeMap<NaiveDate, (Option<Temperature>, Precipitation)>,
+    mean_annual_rainfall: Precipitation,
+) -> BTreeMap<NaiveDate, KbdiState> {
+    let mut projected = BTreeMap::new();
+    let Some(last_day) = forecast_days.keys().next_back().copied() else {
+        return projected;
+    };
+
+    let mut state = current.state;
+    for date in current.date.iter_days().skip(1).take_while(|d| *d <= last_day) {
+        projected.insert(date, state);
+        let (max_temperature, rainfall) = forecast_days
+            .get(&date)
+            .copied()
+            .unwrap_or((None, Precipitation::from_millimeters(0.0)));
+        state = state.next_day(max_temperature, rainfall, mean_annual_rainfall);
+    }
+    projected
+}
@@ -0,0 +1,46 @@
+// Fosberg Fire Weather Index (Fosberg, 1978, "Weather in wildland fire management: the fire
+// weather index"). Combines the equilibrium moisture of fine fuels with wind speed on a 0-100
+// scale; fully dry fuel in a 30 mph wind scores 100 and stronger winds stay at the top.
+
+use super::nfdrs::equilibrium_moisture;
+use crate::units::{Speed, Temperature};
+
+// Scales the index so that m = 0 % and U = 30 mph give 100
+const SCALE: f64 = 0.3002;
+
+pub fn fosberg_index(temperature: Temperature, humidity: f64, wind_speed: Speed) -> f64 {
+    let m = equilibrium_moisture(temperature, humidity) / 30.0;
+    // Moisture damping; fuels at or above 30 % will not carry fire
+    let eta = if m >= 1.0 {
+        0.0
+    } else {
+        1.0 - 2.0 * m + 1.5 * m * m - 0.5 * m * m * m
+    };
+    let u = wind_speed.miles_per_hour();
+    (eta * (1.0 + u * u).sqrt() / SCALE).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fosberg_index_matches_published_scaling() {
+        // By construction, bone-dry fuel (0 % EMC) in a 30 mph wind is 100. Simard's low-humidity
+        // branch gives 0.03229 % at 0 % RH, a damping of 0.9978
+        let dry = fosberg_index(Temperature::from_fahrenheit(70.0), 0.0, Speed::from_miles_per_hour(30.0));
+        assert!((dry - 99.77).abs() < 0.05, "got {}", dry);
+
+        // 70 °F, 40 % RH, 10 mph: EMC 7.597 %, damping 0.5816
+        let moderate = fosberg_index(Temperature::from_fahrenheit(70.0), 40.0, Speed::from_miles_per_hour(10.0));
+        assert!((moderate - 19.47).abs() < 0.05, "got {}", moderate);
+
+        // Saturated air leaves little even in a strong wind: EMC 26.64 %, damping 0.0189
+        let saturated = fosberg_index(Temperature::from_fahrenheit(50.0), 100.0, Speed::from_miles_per_hour(30.0));
+        assert!((saturated - 5.67).abs() < 0.05, "got {}", saturated);
+
+        // Past the top of the scale the index stays at 100
+        let gale = fosberg_index(Temperature::from_fahrenheit(95.0), 5.0, Speed::from_miles_per_hour(60.0));
+        assert_eq!(gale, 100.0);
+    }
+}
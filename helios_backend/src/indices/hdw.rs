@@ -0,0 +1,39 @@
+// Hot-Dry-Windy Index (Srock, Charney, Potter & Goodrick, 2018, Atmosphere 9(7), 279).
+// The product of wind speed (m/s) and vapor pressure deficit (hPa). The published index takes
+// the maximum over the lowest 500 m of a model sounding; from surface observations alone this
+// is the surface value, which runs lower.
+
+use crate::units::{Speed, Temperature};
+
+/// Saturation vapor pressure over water in hPa (Bolton, 1980)
+pub fn saturation_vapor_pressure(temperature: Temperature) -> f64 {
+    let t = temperature.celsius();
+    6.112 * (17.67 * t / (t + 243.5)).exp()
+}
+
+/// Vapor pressure deficit in hPa
+pub fn vapor_pressure_deficit(temperature: Temperature, humidity: f64) -> f64 {
+    saturation_vapor_pressure(temperature) * (1.0 - humidity.clamp(0.0, 100.0) / 100.0)
+}
+
+pub fn hot_dry_windy_index(temperature: Temperature, humidity: f64, wind_speed: Speed) -> f64 {
+    wind_speed.meters_per_second().max(0.0) * vapor_pressure_deficit(temperature, humidity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hot_dry_windy_index_is_wind_times_vapor_pressure_deficit() {
+        // 30 °C gives a saturation vapor pressure of 42.46 hPa; at 10 % RH the deficit is 38.21 hPa
+        let es = saturation_vapor_pressure(Temperature::from_celsius(30.0));
+        assert!((es - 42.46).abs() < 0.01, "got {}", es);
+        let hdw = hot_dry_windy_index(Temperature::from_celsius(30.0), 10.0, Speed::from_meters_per_second(10.0));
+        assert!((hdw - 382.1).abs() < 0.1, "got {}", hdw);
+
+        // Saturated air has no deficit however hard the wind blows
+        let saturated = hot_dry_windy_index(Temperature::from_celsius(30.0), 100.0, Speed::from_meters_per_second(20.0));
+        assert_eq!(saturated, 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+// Keetch-Byram Drought Index (Keetch & Byram, 1968, USDA Forest Service Research Paper SE-38).
+// The index is the soil moisture deficit in hundredths of an inch, from 0 (saturated) to 800.
+
+use crate::units::{Precipitation, Temperature};
+
+pub const KBDI_MAX: f64 = 800.0;
+
+// Where a sensor without history starts. 0 claims saturated soil, which in a dry summer makes
+// the risk model see no drought at all; mid-scale is a moderate deficit until local history or
+// a configured climatological value takes over.
+pub const KBDI_DEFAULT_INITIAL: f64 = 400.0;
+
+// Rain falling in a wet spell is first intercepted by vegetation and litter
+const SPELL_INTERCEPTION_INCHES: f64 = 0.20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KbdiState {
+    pub index: f64,
+    pub spell_rainfall: f64, // Inches since the current run of rainy days began
+}
+
+impl KbdiState {
+    pub fn new(index: f64) -> Self {
+        KbdiState {
+            index: index.clamp(0.0, KBDI_MAX),
+            spell_rainfall: 0.0,
+        }
+    }
+
+    /// Advance the index by one day given that day's maximum temperature and rainfall.
+    /// Without a temperature only the rain is applied.
+    pub fn next_day(
+        self,
+        max_temperature: Option<Temperature>,
+        rainfall: Precipitation,
+        mean_annual_rainfall: Precipitation,
+    ) -> Self {
+        let rain = rainfall.inches().max(0.0);
+
+        // Only rain beyond the first 0.20" of a consecutive-day spell reaches the soil
+        let (spell_rainfall, net_rainfall) = if rain > 0.0 {
+            let spell = self.spell_rainfall + rain;
+            let net = (spell - SPELL_INTERCEPTION_INCHES).max(0.0)
+                - (self.spell_rainfall - SPELL_INTERCEPTION_INCHES).max(0.0);
+            (spell, net)
+        } else {
+            (0.0, 0.0)
+        };
+
+        let wetted = (self.index - net_rainfall * 100.0).max(0.0);
+        let index = match max_temperature {
+            Some(t) => wetted + drought_factor(wetted, t, mean_annual_rainfall),
+            None => wetted,
+        };
+
+        KbdiState {
+            index: index.clamp(0.0, KBDI_MAX),
+            spell_rainfall,
+        }
+    }
+
+    /// The risk model's `drought_index` feature is KBDI rescaled to 0-100
+    pub fn model_input(self) -> f32 {
+        (self.index / 8.0) as f32
+    }
+}
+
+/// Daily increase in the index:
+/// dQ = (800 - Q)(0.968 e^(0.0486 T) - 8.30) / (1 + 10.88 e^(-0.0441 R)) x 10^-3
+/// with T the day's maximum in °F and R the mean annual rainfall in inches.
+/// Cool days (below about 44 °F) dry nothing.
+pub fn drought_factor(index: f64, max_temperature: Temperature, mean_annual_rainfall: Precipitation) -> f64 {
+    let t = max_temperature.fahrenheit();
+    let r = mean_annual_rainfall.inches();
+
+    let evapotranspiration = (0.968 * (0.0486 * t).exp() - 8.30).max(0.0);
+    (KBDI_MAX - index) * evapotranspiration / (1.0 + 10.88 * (-0.0441 * r).exp()) * 1e-3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drought_factor_matches_keetch_byram_equation() {
+        // (Q, max °F, annual rainfall in, expected dQ) worked from the published equation
+        let cases = [
+            (0.0, 90.0, 50.0, 24.922),
+            (400.0, 90.0, 50.0, 12.461),
+            (100.0, 80.0, 20.0, 4.954),
+            (300.0, 100.0, 15.0, 8.813),
+            (200.0, 40.0, 20.0, 0.0),
+        ];
+        for (index, t, r, expected) in cases {
+            let dq = drought_factor(index, Temperature::from_fahrenheit(t), Precipitation::from_inches(r));
+            assert!((dq - expected).abs() < 1e-3, "Q={} T={} R={}: expected {}, got {}", index, t, r, expected, dq);
+        }
+    }
+
+    #[test]
+    fn test_rain_spell_loses_the_first_fifth_of_an_inch() {
+        let annual = Precipitation::from_inches(20.0);
+        let cold = Some(Temperature::from_fahrenheit(30.0)); // No drying, so only rain moves the index
+
+        let day1 = KbdiState::new(300.0).next_day(cold, Precipitation::from_inches(0.15), annual);
+        assert_eq!(day1.index, 300.0);
+        // 0.15 + 0.35 = 0.50" in the spell, 0.30" past the interception threshold
+        let day2 = day1.next_day(cold, Precipitation::from_inches(0.35), annual);
+        assert!((day2.index - 270.0).abs() < 1e-6, "got {}", day2.index);
+        // A dry day ends the spell; the next rain is intercepted again
+        let day3 = day2.next_day(cold, Precipitation::from_inches(0.0), annual);
+        let day4 = day3.next_day(cold, Precipitation::from_inches(0.10), annual);
+        assert!((day4.index - 270.0).abs() < 1e-6, "got {}", day4.index);
+    }
+}
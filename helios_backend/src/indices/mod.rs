@@ -0,0 +1,30 @@
+pub mod daily;
+pub mod fosberg;
+pub mod fwi;
+pub mod hdw;
+pub mod kbdi;
+pub mod nfdrs;
+
+use serde::Serialize;
+
+use crate::clients::WeatherData;
+
+pub use fwi::FwiComponents;
+pub use kbdi::KbdiState;
+
+/// Fire weather indices that need nothing beyond current conditions
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FireWeather {
+    pub fosberg: f64,       // Fosberg Fire Weather Index, 0-100 for winds up to 30 mph
+    pub hot_dry_windy: f64, // Hot-Dry-Windy Index, m/s x hPa, from surface conditions
+}
+
+impl FireWeather {
+    pub fn from_conditions(weather: &WeatherData) -> Self {
+        let humidity = weather.humidity as f64;
+        FireWeather {
+            fosberg: fosberg::fosberg_index(weather.temperature, humidity, weather.wind_speed),
+            hot_dry_windy: hdw::hot_dry_windy_index(weather.temperature, humidity, weather.wind_speed),
+        }
+    }
+}
@@ -0,0 +1,492 @@
+// US National Fire Danger Rating System, 1978 version (Deeming, Burgan & Cohen, 1977, USDA
+// Forest Service GTR INT-39), following the computer implementation in Cohen & Deeming (1985,
+// GTR PSW-82). Works in the system's own units: °F, mph, tons/acre and feet.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::units::{Speed, Temperature};
+
+// Fuel particle properties shared by every model
+const PARTICLE_DENSITY: f64 = 32.0; // lb/ft³
+const HEAT_CONTENT: f64 = 8000.0; // BTU/lb
+const TOTAL_MINERAL: f64 = 0.0555;
+const EFFECTIVE_MINERAL: f64 = 0.01;
+const TONS_PER_ACRE_TO_LB_PER_FT2: f64 = 0.0459137;
+
+// Fine dead fuel moisture while it is raining
+const WET_FINE_FUEL_MOISTURE: f64 = 35.0;
+
+// Start-up moistures for a sensor's first day, before any history has accumulated
+pub const HUNDRED_HOUR_STARTUP: f64 = 20.0;
+pub const THOUSAND_HOUR_STARTUP: f64 = 30.0;
+
+// Days averaged into the 1000-hour boundary condition
+pub const THOUSAND_HOUR_WINDOW: usize = 7;
+
+/// Parsed the same way from `?fuel_model=` and `NFDRS_FUEL_MODEL`, in either case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub enum FuelModel {
+    A, // Western annual grasses
+    C, // Open pine with grass
+    G, // Short-needle conifers, heavy dead load
+    H, // Short-needle conifers, normal dead load
+    L, // Western perennial grasses
+}
+
+/// Fuel bed description for one NFDRS fuel model
+struct FuelBed {
+    // Loadings, tons/acre
+    w1: f64,
+    w10: f64,
+    w100: f64,
+    w1000: f64,
+    wwood: f64,
+    wherb: f64,
+    // Surface-area-to-volume ratios, 1/ft
+    sg1: f64,
+    sg10: f64,
+    sg100: f64,
+    sg1000: f64,
+    sgwood: f64,
+    sgherb: f64,
+    depth: f64, // ft
+    mxd: f64,   // Dead fuel moisture of extinction, %
+    scm: f64,   // Spread component at which the ignition component reaches 100
+    wndfc: f64, // 20 ft to midflame wind adjustment
+}
+
+impl FuelModel {
+    fn bed(self) -> FuelBed {
+        let (w1, w10, w100, w1000, wwood, wherb, sg1, sgherb, depth, mxd, scm, wndfc) = match self {
+            FuelModel::A => (0.20, 0.0, 0.0, 0.0, 0.0, 0.30, 3000.0, 3000.0, 0.80, 15.0, 301.0, 0.6),
+            FuelModel::C => (0.40, 1.0, 0.0, 0.0, 0.50, 0.80, 2000.0, 2500.0, 0.75, 20.0, 32.0, 0.4),
+            FuelModel::G => (2.50, 2.0, 5.0, 12.0, 0.50, 0.50, 2000.0, 2000.0, 1.00, 25.0, 30.0, 0.3),
+            FuelModel::H => (1.50, 1.0, 2.0, 2.0, 0.50, 0.50, 2000.0, 2000.0, 0.30, 20.0, 8.0, 0.3),
+            FuelModel::L => (0.25, 0.0, 0.0, 0.0, 0.0, 0.50, 2000.0, 2000.0, 1.00, 15.0, 178.0, 0.6),
+        };
+        FuelBed {
+            w1,
+            w10,
+            w100,
+            w1000,
+            wwood,
+            wherb,
+            sg1,
+            sg10: 109.0,
+            sg100: 30.0,
+            sg1000: 8.0,
+            sgwood: 1500.0,
+            sgherb,
+            depth,
+            mxd,
+            scm,
+            wndfc,
+        }
+    }
+}
+
+impl FromStr for FuelModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "A" => Ok(FuelModel::A),
+            "C" => Ok(FuelModel::C),
+            "G" => Ok(FuelModel::G),
+            "H" => Ok(FuelModel::H),
+            "L" => Ok(FuelModel::L),
+            other => Err(format!("Unsupported NFDRS fuel model '{}', expected one of A, C, G, H, L", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for FuelModel {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for FuelModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Equilibrium moisture content (%) of wood at a temperature and relative humidity (Simard, 1968)
+pub fn equilibrium_moisture(temperature: Temperature, humidity: f64) -> f64 {
+    let t = temperature.fahrenheit();
+    let h = humidity.clamp(0.0, 100.0);
+    if h < 10.0 {
+        0.03229 + 0.281073 * h - 0.000578 * h * t
+    } else if h < 50.0 {
+        2.22749 + 0.160107 * h - 0.014784 * t
+    } else {
+        21.0606 + 0.005565 * h * h - 0.00035 * h * t - 0.483199 * h
+    }
+}
+
+/// One day of weather driving the 100- and 1000-hour fuel moistures
+#[derive(Debug, Clone, Copy)]
+pub struct DailyConditions {
+    pub min_temperature: Temperature,
+    pub max_temperature: Temperature,
+    pub min_humidity: f64,
+    pub max_humidity: f64,
+    pub wet_hours: f64, // Hours of rain in the day
+    pub latitude: f64,
+    pub day_of_year: u32,
+}
+
+/// Boundary conditions for the day's 100-hour and 1000-hour moisture, in %
+pub fn daily_boundaries(day: &DailyConditions) -> (f64, f64) {
+    let emc_min = equilibrium_moisture(day.max_temperature, day.min_humidity);
+    let emc_max = equilibrium_moisture(day.min_temperature, day.max_humidity);
+
+    let day_length = day_length_hours(day.latitude, day.day_of_year);
+    let emc_bar = (day_length * emc_min + (24.0 - day_length) * emc_max) / 24.0;
+
+    let rain = day.wet_hours.clamp(0.0, 24.0);
+    let hundred_hour = ((24.0 - rain) * emc_bar + rain * (0.5 * rain + 41.0)) / 24.0;
+    let thousand_hour = ((24.0 - rain) * emc_bar + rain * (2.7 * rain + 76.0)) / 24.0;
+    (hundred_hour, thousand_hour)
+}
+
+fn day_length_hours(latitude: f64, day_of_year: u32) -> f64 {
+    let phi = latitude.to_radians();
+    let declination = 0.41008 * ((day_of_year as f64 - 82.0).to_radians()).sin();
+    let cos_hour_angle = (phi.tan() * declination.tan()).clamp(-1.0, 1.0);
+    24.0 * (1.0 - cos_hour_angle.acos() / std::f64::consts::PI)
+}
+
+/// Move yesterday's 100-hour moisture one day towards today's boundary
+pub fn next_hundred_hour(previous: f64, boundary: f64) -> f64 {
+    previous + (boundary - previous) * (1.0 - 0.87 * (-0.24f64).exp())
+}
+
+/// Move the 1000-hour moisture from a week ago towards the week's mean boundary
+pub fn next_thousand_hour(week_ago: f64, mean_boundary: f64) -> f64 {
+    week_ago + (mean_boundary - week_ago) * (1.0 - 0.82 * (-0.168f64).exp())
+}
+
+/// Current weather and fuel state for one calculation
+#[derive(Debug, Clone, Copy)]
+pub struct NfdrsInputs {
+    pub temperature: Temperature,
+    pub humidity: f64,
+    pub wind_speed: Speed, // 20 ft
+    pub raining: bool,
+    pub hundred_hour: f64,
+    pub thousand_hour: f64,
+    pub herbaceous: f64, // Live fuel moistures, %
+    pub woody: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FuelMoisture {
+    pub one_hour: f64,
+    pub ten_hour: f64,
+    pub hundred_hour: f64,
+    pub thousand_hour: f64,
+    pub herbaceous: f64,
+    pub woody: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct NfdrsIndices {
+    pub fuel_model: FuelModel,
+    pub erc: f64, // Energy Release Component, BTU/ft²
+    pub bi: f64,  // Burning Index, tenths of a foot of flame length
+    pub sc: f64,  // Spread Component, ft/min
+    pub ic: f64,  // Ignition Component, % chance a firebrand starts a fire needing suppression
+    pub fuel_moisture: FuelMoisture,
+}
+
+/// Compute ERC, BI, SC and IC for a fuel model. Assumes flat ground.
+pub fn compute(model: FuelModel, inputs: &NfdrsInputs) -> NfdrsIndices {
+    let bed = model.bed();
+
+    let emc = equilibrium_moisture(inputs.temperature, inputs.humidity);
+    let (one_hour, ten_hour) = if inputs.raining {
+        (WET_FINE_FUEL_MOISTURE, WET_FINE_FUEL_MOISTURE)
+    } else {
+        (1.03 * emc, 1.28 * emc)
+    };
+    let moisture = FuelMoisture {
+        one_hour,
+        ten_hour,
+        hundred_hour: inputs.hundred_hour,
+        thousand_hour: inputs.thousand_hour,
+        herbaceous: inputs.herbaceous,
+        woody: inputs.woody,
+    };
+
+    let sc = spread_component(&bed, &moisture, inputs.wind_speed);
+    let erc = energy_release_component(&bed, &moisture);
+    let bi = 3.01 * (sc * erc).powf(0.46);
+    let ic = if inputs.raining {
+        0.0
+    } else {
+        ignition_component(&bed, one_hour, inputs.temperature, sc)
+    };
+
+    NfdrsIndices {
+        fuel_model: model,
+        erc: erc.round(),
+        bi: bi.round(),
+        sc: sc.round(),
+        ic: ic.round(),
+        fuel_moisture: moisture,
+    }
+}
+
+/// Net fuel loads (lb/ft²) after herbaceous curing has moved part of the herb load into 1-hour fuel
+struct Loads {
+    w1: f64,
+    w10: f64,
+    w100: f64,
+    w1000: f64,
+    wwood: f64,
+    wherb: f64,
+}
+
+fn loads(bed: &FuelBed, herbaceous_moisture: f64) -> Loads {
+    let cured = (1.33 - 0.0111 * herbaceous_moisture).clamp(0.0, 1.0);
+    Loads {
+        w1: (bed.w1 + cured * bed.wherb) * TONS_PER_ACRE_TO_LB_PER_FT2,
+        w10: bed.w10 * TONS_PER_ACRE_TO_LB_PER_FT2,
+        w100: bed.w100 * TONS_PER_ACRE_TO_LB_PER_FT2,
+        w1000: bed.w1000 * TONS_PER_ACRE_TO_LB_PER_FT2,
+        wwood: bed.wwood * TONS_PER_ACRE_TO_LB_PER_FT2,
+        wherb: (1.0 - cured) * bed.wherb * TONS_PER_ACRE_TO_LB_PER_FT2,
+    }
+}
+
+/// Rothermel fuel bed quantities for a set of dead classes, each (load, SAV ratio, moisture)
+struct Reaction {
+    intensity: f64, // BTU/ft²/min
+    sgbrt: f64,     // Characteristic SAV ratio
+    betbar: f64,    // Packing ratio
+    betop: f64,     // Optimum packing ratio
+    heat_sink: f64, // BTU/ft³
+}
+
+fn reaction(bed: &FuelBed, dead: &[(f64, f64, f64)], live: &[(f64, f64, f64)], total_category_loads: bool) -> Reaction {
+    let sa_dead: f64 = dead.iter().map(|(w, sg, _)| w / PARTICLE_DENSITY * sg).sum();
+    let sa_live: f64 = live.iter().map(|(w, sg, _)| w / PARTICLE_DENSITY * sg).sum();
+    let sa_total = sa_dead + sa_live;
+    let (f_dead, f_live) = if sa_total > 0.0 { (sa_dead / sa_total, sa_live / sa_total) } else { (0.0, 0.0) };
+    let fraction = |w: f64, sg: f64, total: f64| if total > 0.0 { w / PARTICLE_DENSITY * sg / total } else { 0.0 };
+
+    let mut w_dead_net = 0.0;
+    let mut mc_dead = 0.0;
+    let mut sg_dead = 0.0;
+    let mut hn_dead = 0.0;
+    let mut hn_dead_moisture = 0.0;
+    let mut sink_dead = 0.0;
+    for &(w, sg, mc) in dead {
+        let f = fraction(w, sg, sa_dead);
+        w_dead_net += f * w * (1.0 - TOTAL_MINERAL);
+        mc_dead += f * mc;
+        sg_dead += f * sg;
+        let hn = w * (1.0 - TOTAL_MINERAL) * (-138.0 / sg).exp();
+        hn_dead += hn;
+        hn_dead_moisture += hn * mc;
+        sink_dead += f * (-138.0 / sg).exp() * (250.0 + 11.16 * mc);
+    }
+
+    let mut w_live_net = 0.0;
+    let mut mc_live = 0.0;
+    let mut sg_live = 0.0;
+    let mut hn_live = 0.0;
+    let mut sink_live = 0.0;
+    for &(w, sg, mc) in live {
+        let f = fraction(w, sg, sa_live);
+        w_live_net += f * w * (1.0 - TOTAL_MINERAL);
+        mc_live += f * mc;
+        sg_live += f * sg;
+        hn_live += w * (1.0 - TOTAL_MINERAL) * (-500.0 / sg).exp();
+        sink_live += f * (-138.0 / sg).exp() * (250.0 + 11.16 * mc);
+    }
+
+    // Live fuel moisture of extinction rises with the dead fuel available to dry it out
+    let mxl = if hn_live > 0.0 && hn_dead > 0.0 {
+        let wrat = hn_dead / hn_live;
+        let fine_dead_moisture = hn_dead_moisture / hn_dead;
+        ((2.9 * wrat * (1.0 - fine_dead_moisture / bed.mxd) - 0.226) * 100.0).max(bed.mxd)
+    } else {
+        bed.mxd
+    };
+
+    let damping = |ratio: f64| (1.0 - 2.59 * ratio + 5.11 * ratio.powi(2) - 3.52 * ratio.powi(3)).clamp(0.0, 1.0);
+    let eta_md = damping(mc_dead / bed.mxd);
+    let eta_ml = damping(mc_live / mxl);
+    let eta_s = 0.174 * EFFECTIVE_MINERAL.powf(-0.19);
+
+    let sgbrt = f_dead * sg_dead + f_live * sg_live;
+    let total_load: f64 = dead.iter().chain(live).map(|(w, _, _)| w).sum();
+    let betbar = total_load / (bed.depth * PARTICLE_DENSITY);
+    let betop = 3.348 * sgbrt.powf(-0.8189);
+    let gmamx = sgbrt.powf(1.5) / (495.0 + 0.0594 * sgbrt.powf(1.5));
+    let ad = 133.0 * sgbrt.powf(-0.7913);
+    let gmaop = gmamx * (betbar / betop).powf(ad) * (ad * (1.0 - betbar / betop)).exp();
+
+    // The ERC burns the whole net load of each category, weighted by its share of the surface
+    // area; the spread rate uses the area-weighted load within each category
+    let intensity = if total_category_loads {
+        let dead_net: f64 = dead.iter().map(|(w, _, _)| w * (1.0 - TOTAL_MINERAL)).sum();
+        let live_net: f64 = live.iter().map(|(w, _, _)| w * (1.0 - TOTAL_MINERAL)).sum();
+        gmaop * (f_dead * dead_net * HEAT_CONTENT * eta_s * eta_md + f_live * live_net * HEAT_CONTENT * eta_s * eta_ml)
+    } else {
+        gmaop * (w_dead_net * HEAT_CONTENT * eta_s * eta_md + w_live_net * HEAT_CONTENT * eta_s * eta_ml)
+    };
+
+    let rhobed = total_load / bed.depth;
+    let heat_sink = rhobed * (f_dead * sink_dead + f_live * sink_live);
+
+    Reaction {
+        intensity,
+        sgbrt,
+        betbar,
+        betop,
+        heat_sink,
+    }
+}
+
+fn live_classes(bed: &FuelBed, loads: &Loads, moisture: &FuelMoisture) -> Vec<(f64, f64, f64)> {
+    [(loads.wherb, bed.sgherb, moisture.herbaceous), (loads.wwood, bed.sgwood, moisture.woody)]
+        .into_iter()
+        .filter(|(w, _, _)| *w > 0.0)
+        .collect()
+}
+
+fn spread_component(bed: &FuelBed, moisture: &FuelMoisture, wind_speed: Speed) -> f64 {
+    let loads = loads(bed, moisture.herbaceous);
+    let dead: Vec<(f64, f64, f64)> = [
+        (loads.w1, bed.sg1, moisture.one_hour),
+        (loads.w10, bed.sg10, moisture.ten_hour),
+        (loads.w100, bed.sg100, moisture.hundred_hour),
+    ]
+    .into_iter()
+    .filter(|(w, _, _)| *w > 0.0)
+    .collect();
+    let r = reaction(bed, &dead, &live_classes(bed, &loads, moisture), false);
+    if r.heat_sink <= 0.0 {
+        return 0.0;
+    }
+
+    let zeta = ((0.792 + 0.681 * r.sgbrt.sqrt()) * (r.betbar + 0.1)).exp() / (192.0 + 0.2595 * r.sgbrt);
+
+    // Midflame wind in ft/min, capped where the fire's own intensity limits its effect
+    let midflame = (wind_speed.miles_per_hour() * 88.0 * bed.wndfc).min(0.9 * r.intensity);
+    let b = 0.02526 * r.sgbrt.powf(0.54);
+    let c = 7.47 * (-0.133 * r.sgbrt.powf(0.55)).exp();
+    let e = 0.715 * (-0.000359 * r.sgbrt).exp();
+    let phi_wind = c * midflame.max(0.0).powf(b) * (r.betbar / r.betop).powf(-e);
+
+    r.intensity * zeta * (1.0 + phi_wind) / r.heat_sink
+}
+
+fn energy_release_component(bed: &FuelBed, moisture: &FuelMoisture) -> f64 {
+    let loads = loads(bed, moisture.herbaceous);
+    let dead: Vec<(f64, f64, f64)> = [
+        (loads.w1, bed.sg1, moisture.one_hour),
+        (loads.w10, bed.sg10, moisture.ten_hour),
+        (loads.w100, bed.sg100, moisture.hundred_hour),
+        (loads.w1000, bed.sg1000, moisture.thousand_hour),
+    ]
+    .into_iter()
+    .filter(|(w, _, _)| *w > 0.0)
+    .collect();
+    let r = reaction(bed, &dead, &live_classes(bed, &loads, moisture), true);
+
+    let residence_time = 384.0 / r.sgbrt;
+    0.04 * r.intensity * residence_time
+}
+
+/// Probability of ignition from fine fuel moisture and temperature, scaled by how readily the
+/// fire would spread once started
+fn ignition_component(bed: &FuelBed, one_hour: f64, temperature: Temperature, sc: f64) -> f64 {
+    let t = temperature.celsius();
+    let heat_of_ignition = 144.5 - 0.266 * t - 0.00058 * t * t - 0.01 * t * one_hour
+        + 18.54 * (1.0 - (-0.151 * one_hour).exp())
+        + 6.4 * one_hour;
+    let chi = (344.0 - heat_of_ignition) / 10.0;
+    if chi <= 0.0 || sc <= 0.0 {
+        return 0.0;
+    }
+
+    let p_ignition = (chi.powf(3.66) * 0.000923 / 50.0).clamp(0.0, 1.0);
+    let p_fire_given_ignition = (sc / bed.scm).clamp(0.0, 1.0).sqrt();
+    100.0 * p_ignition * p_fire_given_ignition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(temperature_f: f64, humidity: f64, wind_mph: f64, hundred_hour: f64, thousand_hour: f64) -> NfdrsInputs {
+        NfdrsInputs {
+            temperature: Temperature::from_fahrenheit(temperature_f),
+            humidity,
+            wind_speed: Speed::from_miles_per_hour(wind_mph),
+            raining: false,
+            hundred_hour,
+            thousand_hour,
+            herbaceous: 60.0,
+            woody: 90.0,
+        }
+    }
+
+    #[test]
+    fn test_fuel_models_deserialize_like_they_parse() {
+        assert_eq!(serde_json::from_str::<FuelModel>("\"g\"").unwrap(), FuelModel::G);
+        assert_eq!(serde_json::from_str::<FuelModel>("\"L\"").unwrap(), "l".parse().unwrap());
+        assert!(serde_json::from_str::<FuelModel>("\"Z\"").is_err());
+    }
+
+    #[test]
+    fn test_moisture_boundaries_follow_the_published_equations() {
+        // Simard's middle humidity band: 2.22749 + 0.160107 * 40 - 0.014784 * 70
+        let emc = equilibrium_moisture(Temperature::from_fahrenheit(70.0), 40.0);
+        assert!((emc - 7.597).abs() < 1e-3, "got {}", emc);
+
+        // A day of continuous rain sets the boundaries to their rain-only values
+        let (hundred_hour, thousand_hour) = daily_boundaries(&DailyConditions {
+            min_temperature: Temperature::from_fahrenheit(50.0),
+            max_temperature: Temperature::from_fahrenheit(60.0),
+            min_humidity: 80.0,
+            max_humidity: 100.0,
+            wet_hours: 24.0,
+            latitude: 42.2,
+            day_of_year: 300,
+        });
+        assert!((hundred_hour - 53.0).abs() < 1e-9);
+        assert!((thousand_hour - 140.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_indices_rise_as_fuels_dry_and_drop_to_no_ignition_in_rain() {
+        let mild = compute(FuelModel::G, &inputs(70.0, 40.0, 5.0, 15.0, 20.0));
+        let severe = compute(FuelModel::G, &inputs(95.0, 8.0, 20.0, 5.0, 7.0));
+        assert!(severe.erc > mild.erc);
+        assert!(severe.sc > mild.sc);
+        assert!(severe.bi > mild.bi);
+        assert!(severe.ic > mild.ic);
+        assert!((0.0..=100.0).contains(&severe.ic));
+
+        // Grass spreads faster than timber litter but releases far less energy
+        let grass = compute(FuelModel::L, &inputs(95.0, 8.0, 20.0, 5.0, 7.0));
+        assert!(grass.sc > severe.sc);
+        assert!(grass.erc < severe.erc);
+
+        let raining = compute(FuelModel::G, &NfdrsInputs { raining: true, ..inputs(95.0, 8.0, 20.0, 5.0, 7.0) });
+        assert_eq!(raining.ic, 0.0);
+        assert_eq!(raining.fuel_moisture.one_hour, 35.0);
+        assert!(raining.erc < severe.erc);
+    }
+}
@@ -0,0 +1,291 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Timelike, Utc};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::clients::{
+    airnow::{aqi_category_name, aqi_category_number, AirQualityObservation},
+    AirQualityIndex, AirQualityProvider, ProviderError, WeatherData, WeatherProvider,
+};
+use crate::config::IngestionConfig;
+use crate::database::Database;
+use crate::models::{
+    NewIngestionRun, NewSensorReading, SensorLocation, SensorReading, PARAM_AQI, PARAM_HUMIDITY, PARAM_PRECIPITATION,
+    PARAM_TEMPERATURE, PARAM_WIND_DIRECTION, PARAM_WIND_SPEED,
+};
+use crate::units::{Precipitation, Speed, Temperature};
+
+pub const SOURCE_AIRNOW: &str = "AirNow";
+pub const SOURCE_OPENWEATHERMAP: &str = "OpenWeatherMap";
+
+// Keep the stored error list readable when an upstream is down for every sensor
+const MAX_RECORDED_ERRORS: usize = 50;
+
+// Timeouts and 5xx responses are retried with a doubling delay
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Start the polling loop on the tokio runtime. The first pass runs immediately.
+pub fn spawn(
+    db: Arc<Database>,
+    air_quality: Arc<dyn AirQualityProvider>,
+    weather: Arc<dyn WeatherProvider>,
+    config: IngestionConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let run = run_once(&db, air_quality.as_ref(), weather.as_ref()).await;
+            println!(
+                "Ingestion run: {}/{} sensors succeeded, {} readings stored",
+                run.sensors_succeeded, run.sensors_total, run.readings_inserted
+            );
+
+            if let Err(e) = db.record_ingestion_run(&run).await {
+                eprintln!("Failed to record ingestion run: {}", e);
+            }
+        }
+    })
+}
+
+/// Fetch current observations for every active sensor and store them.
+pub async fn run_once(
+    db: &Database,
+    air_quality_provider: &dyn AirQualityProvider,
+    weather_provider: &dyn WeatherProvider,
+) -> NewIngestionRun {
+    let mut run = NewIngestionRun {
+        started_at: Utc::now(),
+        ..Default::default()
+    };
+
+    let sensors = match db.get_active_sensors().await {
+        Ok(sensors) => sensors,
+        Err(e) => {
+            run.errors.push(format!("Failed to load sensors: {}", e));
+            run.finished_at = Utc::now();
+            return run;
+        }
+    };
+    run.sensors_total = sensors.len() as i32;
+
+    for sensor in &sensors {
+        let (air_quality, weather) = tokio::join!(
+            with_retry(|| air_quality_provider.current_by_coords(sensor.latitude, sensor.longitude)),
+            with_retry(|| weather_provider.current(sensor.latitude, sensor.longitude))
+        );
+
+        // Without an API key every sensor would fail the same way, so stop here
+        if let Some(e) = [air_quality.as_ref().err(), weather.as_ref().err()]
+            .into_iter()
+            .flatten()
+            .find(|e| matches!(e, ProviderError::MissingApiKey(_)))
+        {
+            run.errors.push(format!("Aborting run: {}", e));
+            run.sensors_failed = run.sensors_total - run.sensors_succeeded;
+            break;
+        }
+
+        let mut readings = Vec::new();
+        let mut sensor_errors = Vec::new();
+
+        match air_quality {
+            Ok(aqi) => readings.extend(air_quality_readings(sensor.id, &aqi, run.started_at)),
+            Err(e) => sensor_errors.push(format!("air quality: {}", e)),
+        }
+        match weather {
+            Ok(weather) => readings.extend(weather_readings(sensor.id, &weather)),
+            Err(e) => sensor_errors.push(format!("weather: {}", e)),
+        }
+
+        match db.insert_readings(&readings).await {
+            Ok(inserted) => run.readings_inserted += inserted as i64,
+            Err(e) => sensor_errors.push(format!("database: {}", e)),
+        }
+
+        if sensor_errors.is_empty() {
+            run.sensors_succeeded += 1;
+        } else {
+            run.sensors_failed += 1;
+            for error in sensor_errors {
+                if run.errors.len() < MAX_RECORDED_ERRORS {
+                    run.errors.push(format!("sensor {} ({}): {}", sensor.id, sensor.name, error));
+                }
+            }
+        }
+    }
+
+    run.finished_at = Utc::now();
+    run
+}
+
+async fn with_retry<T, F, Fut>(mut call: F) -> Result<T, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ProviderError>>,
+{
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if e.is_retryable() && attempt < MAX_ATTEMPTS => {
+                eprintln!("Retrying after attempt {} failed: {}", attempt, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn air_quality_readings(sensor_id: i32, aqi: &AirQualityIndex, fetched_at: DateTime<Utc>) -> Vec<NewSensorReading> {
+    // Fall back to the top of the current hour so repeated polls still dedupe
+    let top_of_hour = fetched_at
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(fetched_at);
+    let reading = |parameter: &str, value: i32, observed_at: Option<DateTime<Utc>>| NewSensorReading {
+        sensor_id,
+        parameter: parameter.to_string(),
+        value: None,
+        unit: None,
+        aqi: Some(value),
+        observed_at: observed_at.unwrap_or(top_of_hour),
+        source: SOURCE_AIRNOW.to_string(),
+    };
+
+    std::iter::once(reading(PARAM_AQI, aqi.aqi, aqi.observed_at))
+        .chain(aqi.pollutants.iter().map(|p| reading(&p.parameter, p.aqi, p.observed_at)))
+        .collect()
+}
+
+fn weather_readings(sensor_id: i32, weather: &WeatherData) -> Vec<NewSensorReading> {
+    let precipitation = weather.precipitation_last_hour
+        .map(|p| (PARAM_PRECIPITATION, p.millimeters(), "mm"));
+
+    [
+        (PARAM_TEMPERATURE, weather.temperature.celsius(), "C"),
+        (PARAM_HUMIDITY, weather.humidity as f64, "%"),
+        (PARAM_WIND_SPEED, weather.wind_speed.meters_per_second(), "m/s"),
+        (PARAM_WIND_DIRECTION, weather.wind_direction, "deg"),
+    ]
+    .into_iter()
+    .chain(precipitation)
+    .map(|(parameter, value, unit)| NewSensorReading {
+        sensor_id,
+        parameter: parameter.to_string(),
+        value: Some(value),
+        unit: Some(unit.to_string()),
+        aqi: None,
+        observed_at: weather.observed_at,
+        source: SOURCE_OPENWEATHERMAP.to_string(),
+    })
+    .collect()
+}
+
+/// Latest stored AQI from the nearest sensor that has one newer than `cutoff`.
+/// Sensors are expected in nearest-first order, as returned by `get_sensors_near_point`.
+pub fn stored_air_quality(sensors: &[SensorLocation], cutoff: DateTime<Utc>) -> Option<AirQualityIndex> {
+    sensors.iter().find_map(|sensor| {
+        let reading = fresh_reading(&sensor.latest_readings, PARAM_AQI, cutoff)?;
+        let aqi = reading.aqi?;
+
+        let pollutants: Vec<AirQualityObservation> = sensor.latest_readings
+            .iter()
+            .filter(|r| r.source == SOURCE_AIRNOW && r.parameter != PARAM_AQI && r.observed_at >= cutoff)
+            .filter_map(|r| {
+                let aqi = r.aqi?;
+                Some(AirQualityObservation {
+                    parameter: r.parameter.clone(),
+                    aqi,
+                    category: aqi_category_name(aqi).to_string(),
+                    category_number: aqi_category_number(aqi),
+                    observed_at: Some(r.observed_at),
+                })
+            })
+            .collect();
+
+        // Readings stored before pollutants were tracked only have the overall AQI
+        AirQualityIndex::from_observations(pollutants, Some(sensor.name.clone()), None)
+            .or_else(|| Some(AirQualityIndex {
+                aqi,
+                category: aqi_category_name(aqi).to_string(),
+                observed_at: Some(reading.observed_at),
+                reporting_area: Some(sensor.name.clone()),
+                state_code: None,
+                dominant_pollutant: None,
+                pollutants: Vec::new(),
+            }))
+    })
+}
+
+/// Latest stored weather from the nearest sensor with a complete, fresh set of readings.
+pub fn stored_weather(sensors: &[SensorLocation], cutoff: DateTime<Utc>) -> Option<WeatherData> {
+    sensors.iter().find_map(|sensor| {
+        let reading = |parameter| fresh_reading(&sensor.latest_readings, parameter, cutoff);
+        let value = |parameter| reading(parameter).and_then(|r| r.value);
+
+        // Stored values carry their unit; anything we cannot interpret is skipped
+        let temperature_reading = reading(PARAM_TEMPERATURE)?;
+        let temperature = Temperature::from_unit(
+            temperature_reading.value?,
+            temperature_reading.unit.as_deref().unwrap_or("C"),
+        )?;
+        let wind_speed_reading = reading(PARAM_WIND_SPEED)?;
+        let wind_speed = Speed::from_unit(
+            wind_speed_reading.value?,
+            wind_speed_reading.unit.as_deref().unwrap_or("m/s"),
+        )?;
+
+        Some(WeatherData {
+            temperature,
+            humidity: value(PARAM_HUMIDITY)?.round().clamp(0.0, 100.0) as u8,
+            wind_speed,
+            wind_direction: value(PARAM_WIND_DIRECTION)?,
+            precipitation_last_hour: value(PARAM_PRECIPITATION).map(Precipitation::from_millimeters),
+            observed_at: temperature_reading.observed_at,
+        })
+    })
+}
+
+fn fresh_reading<'a>(readings: &'a [SensorReading], parameter: &str, cutoff: DateTime<Utc>) -> Option<&'a SensorReading> {
+    readings
+        .iter()
+        .filter(|r| r.parameter == parameter && r.observed_at >= cutoff)
+        .max_by_key(|r| r.observed_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HourlyPrecipitation;
+    use crate::precipitation::{self, PrecipitationHistory};
+    use crate::units::UnitSystem;
+
+    #[test]
+    fn test_dry_weather_hour_counts_toward_precipitation_coverage() {
+        let dry = r#"{"main": {"temp": 20.0, "humidity": 40}, "wind": {"speed": 3.0, "deg": 180.0}, "dt": 1755205200}"#;
+        let weather = crate::clients::weather::parse_current_weather(dry).unwrap();
+
+        let readings = weather_readings(7, &weather);
+        let rain = readings.iter().find(|r| r.parameter == PARAM_PRECIPITATION).expect("a dry hour is stored as 0 mm");
+        assert_eq!(rain.value, Some(0.0));
+
+        let history = PrecipitationHistory::new(7, vec![HourlyPrecipitation {
+            sensor_id: 7,
+            hour: rain.observed_at,
+            millimeters: 0.0,
+        }]);
+        let summary = precipitation::coverage(Some(&history), weather.observed_at, UnitSystem::Imperial);
+        assert_eq!(summary.hours_covered, 1);
+        assert_eq!(summary.total, 0.0);
+    }
+}
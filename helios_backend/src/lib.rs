@@ -0,0 +1,3 @@
+// The database layer, shared by the server and the seed_sensors importer
+pub mod database;
+pub mod models;
@@ -1,9 +1,10 @@
 use axum::{
-    routing::get,
+    routing::{get, post},
     Router,
     Json,
-    extract::{Path, Query, State},
-    http::Method,
+    extract::{Path, Query, State, rejection::{JsonRejection, PathRejection, QueryRejection}},
+    http::{header, header::AUTHORIZATION, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -12,20 +13,39 @@ use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::cors::{CorsLayer, Any};
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 
 mod config;
 mod clients;
-mod models;
-mod database;
+mod error;
+mod geojson;
+mod grid;
+mod indices;
+mod ingestion;
 mod ml;
+mod precipitation;
+mod risk_layer;
+mod tiles;
+mod units;
 
+use clients::{AirQualityProvider, ForecastCache, WeatherProvider};
+use config::{IndicesConfig, IngestionConfig, ModelConfig, ProviderConfig, RiskLayerConfig, TileConfig};
+use helios_backend::{database, models};
 use database::Database;
-use ml::{RiskModel, WeatherData};
+use error::ApiError;
+use ml::{ModelMetadata, ModelRegistry, WeatherData};
+use units::UnitSystem;
 
 #[derive(Deserialize)]
 struct LocationQuery {
     lat: Option<f64>,
     lon: Option<f64>,
+    units: Option<UnitSystem>,
+}
+
+#[derive(Deserialize)]
+struct UnitsQuery {
+    units: Option<UnitSystem>,
 }
 
 #[derive(Deserialize)]
@@ -36,18 +56,66 @@ struct BoundsQuery {
     max_lon: Option<f64>,
     lat: Option<f64>,
     lon: Option<f64>,
+    format: Option<geojson::Format>,
+}
+
+#[derive(Deserialize)]
+struct SensorDetailQuery {
+    hours: Option<i64>, // Recent readings to include; defaults to the last 24 hours
+}
+
+#[derive(Deserialize)]
+struct ReadingsQuery {
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    parameter: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ForecastQuery {
+    zip: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    date: Option<chrono::NaiveDate>, // Issue date; defaults to today
 }
 
 #[derive(Deserialize)]
 struct RiskQuery {
     lat: f64,
     lon: f64,
+    units: Option<UnitSystem>,
+    fuel_model: Option<indices::nfdrs::FuelModel>, // NFDRS fuel model; defaults to NFDRS_FUEL_MODEL
+    format: Option<geojson::Format>,
+}
+
+#[derive(Deserialize)]
+struct GridQuery {
+    min_lat: f64,
+    min_lon: f64,
+    max_lat: f64,
+    max_lon: f64,
+    resolution: Option<f64>, // Cell size in degrees
+    format: Option<geojson::Format>,
+}
+
+#[derive(Deserialize)]
+struct FwiQuery {
+    lat: f64,
+    lon: f64,
+    days: Option<i64>, // History to include; defaults to a week
 }
 
 #[derive(Clone)]
 struct AppState {
     db: Arc<Database>,
-    risk_model: Arc<RiskModel>,
+    models: Arc<ModelRegistry>,
+    admin_token: Option<String>,
+    air_quality: Arc<dyn AirQualityProvider>,
+    weather: Arc<dyn WeatherProvider>,
+    forecast_cache: Arc<ForecastCache>,
+    tile_cache: Arc<tiles::TileCache>,
+    indices: IndicesConfig,
+    max_reading_age: chrono::Duration, // Older stored readings fall back to the live APIs
 }
 
 #[derive(Serialize)]
@@ -105,13 +173,72 @@ async fn main() {
     
     let db = Arc::new(Database::new(pool));
     
-    // Initialize the ML risk model
-    let risk_model = Arc::new(RiskModel::new().expect("Failed to load risk model"));
+    // Load the active risk model from the registry; SIGHUP or the admin endpoint swaps it
+    let model_config = ModelConfig::from_env().expect("Invalid model configuration");
+    let models = Arc::new(
+        ModelRegistry::open(&model_config.registry_dir, model_config.load_options()).expect("Failed to load risk model"),
+    );
+    println!(
+        "Serving risk model {} from {}",
+        models.active().metadata.version,
+        model_config.registry_dir.display()
+    );
+    #[cfg(unix)]
+    ml::registry::reload_on_hangup(models.clone()).expect("Failed to listen for SIGHUP");
+    
+    // Upstream data providers: live APIs, or recorded fixtures for offline use
+    let provider_config = ProviderConfig::from_env().expect("Invalid provider configuration");
+    let clients::Providers { air_quality, weather } = clients::providers_from_env(&provider_config)
+        .expect("AIRNOW_API_KEY and WEATHER_API_KEY must be set in .env file unless PROVIDER_MODE=replay");
+    println!("Using {:?} data providers (fixtures in {})", provider_config.mode, provider_config.fixtures_dir);
     
-    let app_state = AppState { db, risk_model };
+    // Poll upstream providers for every registered sensor in the background
+    let ingestion_config = IngestionConfig::from_env();
+    if ingestion_config.enabled {
+        println!("Starting ingestion worker (every {}s)", ingestion_config.interval.as_secs());
+        ingestion::spawn(db.clone(), air_quality.clone(), weather.clone(), ingestion_config.clone());
+    }
+
+    // Roll daily fire danger indices forward from the stored readings
+    let indices_config = IndicesConfig::from_env().expect("Invalid indices configuration");
+    if indices_config.enabled {
+        println!("Starting daily indices job");
+        indices::daily::spawn(db.clone(), indices_config.clone());
+    }
+
+    // Score the stored risk layer drawn by the risk map tiles
+    let risk_layer_config = RiskLayerConfig::from_env().expect("Invalid risk layer configuration");
+    if risk_layer_config.enabled {
+        println!(
+            "Starting risk layer job (every {}s at {} degrees)",
+            risk_layer_config.interval.as_secs(),
+            risk_layer_config.resolution
+        );
+        risk_layer::spawn(
+            db.clone(),
+            models.clone(),
+            indices_config.clone(),
+            risk_layer_config,
+            ingestion_config.max_reading_age(),
+        );
+    }
+    
+    let tile_config = TileConfig::from_env();
+
+    let app_state = AppState {
+        db,
+        models,
+        admin_token: model_config.admin_token,
+        air_quality,
+        weather,
+        forecast_cache: Arc::new(ForecastCache::new(provider_config.forecast_cache_ttl)),
+        tile_cache: Arc::new(tiles::TileCache::new(tile_config.cache_ttl, tile_config.max_cached)),
+        indices: indices_config,
+        max_reading_age: ingestion_config.max_reading_age(),
+    };
     
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST])
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
         .allow_headers(Any)
         .allow_origin(Any);
     
@@ -119,8 +246,21 @@ async fn main() {
         .route("/environmental-data", get(environmental_data_handler))
         .route("/health", get(health_handler))
         .route("/api/status/:zipcode", get(status_handler)) // Keep old endpoint for compatibility
-        .route("/api/sensors", get(sensors_handler))
+        .route("/api/sensors", get(sensors_handler).post(create_sensor_handler))
+        .route(
+            "/api/sensors/:id",
+            get(sensor_detail_handler).patch(update_sensor_handler).delete(decommission_sensor_handler),
+        )
+        .route("/api/sensors/:id/readings", get(sensor_readings_handler))
+        .route("/api/air-quality/forecast", get(air_quality_forecast_handler))
         .route("/api/risk/point", get(risk_prediction_handler))
+        .route("/api/risk/forecast", get(risk_forecast_handler))
+        .route("/api/risk/grid", get(risk_grid_handler))
+        .route("/tiles/:layer/:z/:x/:y", get(tile_handler))
+        .route("/api/indices/fwi", get(fwi_handler))
+        .route("/api/ingestion/runs", get(ingestion_runs_handler))
+        .route("/api/admin/models", get(models_handler))
+        .route("/api/admin/models/reload", post(reload_model_handler))
         .with_state(app_state)
         .layer(ServiceBuilder::new().layer(cors));
 
@@ -130,8 +270,15 @@ async fn main() {
     println!("  GET /health - Health check");
     println!("  GET /environmental-data?lat=44.1&lon=-121.7 - Environmental data");
     println!("  GET /api/sensors?min_lat=44&min_lon=-122&max_lat=45&max_lon=-121 - Sensors in bounds");
+    println!("  GET /api/sensors/:id - Sensor details with recent readings (POST /api/sensors, PATCH and DELETE /api/sensors/:id to manage)");
+    println!("  GET /api/sensors/:id/readings?start=2025-06-01T00:00:00Z&end=2025-06-02T00:00:00Z - Sensor history");
     println!("  GET /api/status/:zipcode - Legacy status endpoint");
+    println!("  GET /api/air-quality/forecast?lat=44.1&lon=-121.7 (or ?zip=97601) - AirNow AQI forecast");
     println!("  GET /api/risk/point?lat=44.1&lon=-121.7 - Fire risk prediction");
+    println!("  GET /api/risk/forecast?lat=44.1&lon=-121.7 - Fire risk timeline for the next 5 days");
+    println!("  GET /api/risk/grid?min_lat=44&min_lon=-122&max_lat=44.5&max_lon=-121.5 - Fire risk over a grid");
+    println!("  GET /tiles/:layer/:z/:x/:y.mvt - sensors, readings or risk map layer as a Mapbox Vector Tile");
+    println!("  GET /api/ingestion/runs - Recent ingestion worker runs");
     
     axum::serve(listener, app).await.unwrap();
 }
@@ -139,114 +286,114 @@ async fn main() {
 #[axum::debug_handler]
 async fn environmental_data_handler(
     State(state): State<AppState>,
-    Query(params): Query<LocationQuery>
-) -> Json<serde_json::Value> {
+    query: Result<Query<LocationQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Query(params) = query?;
     // Use provided coordinates or default to Altamont, Oregon
     let (lat, lon) = (
         params.lat.unwrap_or(44.1292),
         params.lon.unwrap_or(-121.7689)
     );
-    
-    // For now, use zip code 97601 (Klamath Falls, OR) as a proxy for the region
-    let zip_code = "97601";
-
-    let air_quality_future = clients::get_aqi_by_zip(zip_code);
-    let weather_future = clients::get_weather_by_coords(lat, lon);
-    let sensors_future = state.db.get_sensors_near_point(lat, lon, 25.0);
-
-    let (air_quality, weather, sensors) = tokio::join!(air_quality_future, weather_future, sensors_future);
-
-    let nearby_sensors = sensors.unwrap_or_default();
-
-    match (air_quality, weather) {
-        (Ok(aqi_data), Ok(weather_data)) => {
-            Json(json!({
-                "air_quality": {
-                    "aqi": aqi_data.aqi,
-                    "category": aqi_data.category,
-                    "location": format!("Altamont, Oregon"),
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                },
-                "weather": {
-                    "temperature": weather_data.temperature,
-                    "humidity": weather_data.humidity,
-                    "wind_speed": weather_data.wind_speed,
-                    "wind_direction": weather_data.wind_direction
-                },
-                "location": {
-                    "latitude": lat,
-                    "longitude": lon
-                },
-                "sensors": nearby_sensors
-            }))
+    validate_coordinates(lat, lon)?;
+    let units = params.units.unwrap_or_default();
+
+    let nearby_sensors = state.db.get_sensors_near_point(lat, lon, 25.0).await?;
+    environmental_data(&state, lat, lon, units, nearby_sensors).await
+}
+
+/// Current conditions at a point, from what `nearby_sensors` stored or the live providers
+async fn environmental_data(
+    state: &AppState,
+    lat: f64,
+    lon: f64,
+    units: UnitSystem,
+    nearby_sensors: Vec<models::SensorLocation>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    // Prefer what the ingestion worker already stored; only hit the live APIs when it is stale
+    let cutoff = chrono::Utc::now() - state.max_reading_age;
+    let stored_aqi = ingestion::stored_air_quality(&nearby_sensors, cutoff);
+    let stored_weather = ingestion::stored_weather(&nearby_sensors, cutoff);
+    let data_sources = json!({
+        "air_quality": if stored_aqi.is_some() { "stored" } else { "live" },
+        "weather": if stored_weather.is_some() { "stored" } else { "live" }
+    });
+
+    let air_quality_future = async {
+        match stored_aqi {
+            Some(aqi_data) => Ok(aqi_data),
+            None => state.air_quality.current_by_coords(lat, lon).await,
         }
-        (Err(aqi_err), Ok(weather_data)) => {
-            Json(json!({
-                "air_quality": {
-                    "aqi": 0,
-                    "category": "Data Unavailable",
-                    "location": "Altamont, Oregon",
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                },
-                "weather": {
-                    "temperature": weather_data.temperature,
-                    "humidity": weather_data.humidity,
-                    "wind_speed": weather_data.wind_speed,
-                    "wind_direction": weather_data.wind_direction
-                },
-                "location": {
-                    "latitude": lat,
-                    "longitude": lon
-                },
-                "sensors": nearby_sensors,
-                "error": format!("Failed to fetch air quality data: {}", aqi_err)
-            }))
+    };
+    let weather_future = async {
+        match stored_weather {
+            Some(weather_data) => Ok(weather_data),
+            None => state.weather.current(lat, lon).await,
         }
-        (Ok(aqi_data), Err(weather_err)) => {
-            Json(json!({
-                "air_quality": {
-                    "aqi": aqi_data.aqi,
-                    "category": aqi_data.category,
-                    "location": "Altamont, Oregon",
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                },
-                "weather": {
-                    "temperature": 0.0,
-                    "humidity": 0.0,
-                    "wind_speed": 0.0,
-                    "wind_direction": 0.0
-                },
-                "location": {
-                    "latitude": lat,
-                    "longitude": lon
-                },
-                "sensors": nearby_sensors,
-                "error": format!("Failed to fetch weather data: {}", weather_err)
-            }))
+    };
+
+    let (air_quality, weather) = tokio::join!(air_quality_future, weather_future);
+
+    // Serve whatever succeeded; only fail the whole request when every upstream failed
+    if let (Err(aqi_err), Err(weather_err)) = (&air_quality, &weather) {
+        return Err(ApiError::UpstreamUnavailable(format!(
+            "Failed to fetch data - AQI: {}, Weather: {}", aqi_err, weather_err
+        )));
+    }
+
+    // Name the reporting area the AQI came from; fall back to the requested point
+    let coordinates_label = format!("{:.4}, {:.4}", lat, lon);
+
+    let mut errors = Vec::new();
+    let air_quality = match air_quality {
+        Ok(aqi_data) => json!({
+            "aqi": aqi_data.aqi,
+            "category": aqi_data.category,
+            "dominant_pollutant": aqi_data.dominant_pollutant,
+            "pollutants": aqi_data.pollutants,
+            "location": aqi_data.location().unwrap_or(coordinates_label),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }),
+        Err(aqi_err) => {
+            errors.push(ApiError::UpstreamUnavailable(format!("Failed to fetch air quality data: {}", aqi_err)).body());
+            json!({
+                "aqi": 0,
+                "category": "Data Unavailable",
+                "dominant_pollutant": null,
+                "pollutants": [],
+                "location": coordinates_label,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            })
         }
-        (Err(aqi_err), Err(weather_err)) => {
-            Json(json!({
-                "air_quality": {
-                    "aqi": 0,
-                    "category": "Data Unavailable",
-                    "location": "Altamont, Oregon",
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                },
-                "weather": {
-                    "temperature": 0.0,
-                    "humidity": 0.0,
-                    "wind_speed": 0.0,
-                    "wind_direction": 0.0
-                },
-                "location": {
-                    "latitude": lat,
-                    "longitude": lon
-                },
-                "sensors": nearby_sensors,
-                "error": format!("Failed to fetch data - AQI: {}, Weather: {}", aqi_err, weather_err)
-            }))
+    };
+    let weather = match weather {
+        Ok(weather_data) => weather_json(&weather_data, units),
+        Err(weather_err) => {
+            errors.push(ApiError::UpstreamUnavailable(format!("Failed to fetch weather data: {}", weather_err)).body());
+            json!({
+                "temperature": 0.0,
+                "humidity": 0.0,
+                "wind_speed": 0.0,
+                "wind_direction": 0.0
+            })
         }
+    };
+
+    let mut response = json!({
+        "air_quality": air_quality,
+        "weather": weather,
+        "location": {
+            "latitude": lat,
+            "longitude": lon
+        },
+        "units": units.labels(),
+        "sensors": nearby_sensors,
+        "data_sources": data_sources
+    });
+    if !errors.is_empty() {
+        response["errors"] = json!(errors);
     }
+
+    Ok(Json(response))
 }
 
 async fn health_handler() -> Json<serde_json::Value> {
@@ -257,173 +404,871 @@ async fn health_handler() -> Json<serde_json::Value> {
 }
 
 #[axum::debug_handler]
-async fn status_handler(Path(zip_code): Path<String>) -> Json<serde_json::Value> {
+async fn status_handler(
+    State(state): State<AppState>,
+    path: Result<Path<String>, PathRejection>,
+    query: Result<Query<UnitsQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Path(zip_code) = path?;
+    let Query(params) = query?;
     let (lat, lon) = (42.19, -121.78); // Hardcoded coordinates for Altamont
+    let units = params.units.unwrap_or_default();
 
-    let air_quality_future = clients::get_aqi_by_zip(&zip_code);
-    let weather_future = clients::get_weather_by_coords(lat, lon);
+    let air_quality_future = state.air_quality.current_by_zip(&zip_code);
+    let weather_future = state.weather.current(lat, lon);
 
     let (air_quality, weather) = tokio::join!(air_quality_future, weather_future);
 
-    match (air_quality, weather) {
-        (Ok(aqi_data), Ok(weather_data)) => {
-            Json(json!({
-                "aqi": aqi_data.aqi,
-                "aqi_category": aqi_data.category,
-                "temperature": weather_data.temperature,
-                "humidity": weather_data.humidity,
-                "wind_speed": weather_data.wind_speed,
-                "wind_direction": weather_data.wind_direction
-            }))
-        }
-        (Err(aqi_err), Ok(weather_data)) => {
-            Json(json!({
-                "error": format!("Failed to fetch air quality data: {}", aqi_err),
-                "temperature": weather_data.temperature,
-                "humidity": weather_data.humidity,
-                "wind_speed": weather_data.wind_speed,
-                "wind_direction": weather_data.wind_direction
-            }))
-        }
-        (Ok(aqi_data), Err(weather_err)) => {
-            Json(json!({
-                "aqi": aqi_data.aqi,
-                "aqi_category": aqi_data.category,
-                "error": format!("Failed to fetch weather data: {}", weather_err)
-            }))
+    let mut response = json!({ "units": units.labels() });
+    let mut errors = Vec::new();
+
+    match air_quality {
+        Ok(aqi_data) => {
+            response["aqi"] = json!(aqi_data.aqi);
+            response["aqi_category"] = json!(aqi_data.category);
+            response["dominant_pollutant"] = json!(aqi_data.dominant_pollutant);
+            response["pollutants"] = json!(aqi_data.pollutants);
+            response["location"] = json!(aqi_data.location());
         }
-        (Err(aqi_err), Err(weather_err)) => {
-            Json(json!({
-                "error": format!("Failed to fetch data - AQI: {}, Weather: {}", aqi_err, weather_err)
-            }))
+        Err(aqi_err) => errors.push(format!("Failed to fetch air quality data: {}", aqi_err)),
+    }
+    match weather {
+        Ok(weather_data) => {
+            response["temperature"] = json!(weather_data.temperature.in_units(units));
+            response["humidity"] = json!(weather_data.humidity);
+            response["wind_speed"] = json!(weather_data.wind_speed.in_units(units));
+            response["wind_direction"] = json!(weather_data.wind_direction);
         }
+        Err(weather_err) => errors.push(format!("Failed to fetch weather data: {}", weather_err)),
+    }
+
+    match errors.len() {
+        0 => {}
+        1 => response["errors"] = json!([ApiError::UpstreamUnavailable(errors.remove(0)).body()]),
+        _ => return Err(ApiError::UpstreamUnavailable(errors.join("; "))),
     }
+
+    Ok(Json(response))
 }
 
 #[axum::debug_handler]
 async fn sensors_handler(
     State(state): State<AppState>,
-    Query(params): Query<BoundsQuery>
-) -> Json<serde_json::Value> {
+    headers: HeaderMap,
+    query: Result<Query<BoundsQuery>, QueryRejection>
+) -> Result<geojson::Negotiated, ApiError> {
+    let Query(params) = query?;
+    let format = geojson::Format::negotiate(params.format, &headers);
+
     // If bounds are provided, use them; otherwise get all sensors
-    let sensors_result = if let (Some(min_lat), Some(min_lon), Some(max_lat), Some(max_lon)) = 
+    let sensors = if let (Some(min_lat), Some(min_lon), Some(max_lat), Some(max_lon)) = 
         (params.min_lat, params.min_lon, params.max_lat, params.max_lon) {
-        state.db.get_sensors_in_bounds(min_lat, min_lon, max_lat, max_lon).await
+        validate_coordinates(min_lat, min_lon)?;
+        validate_coordinates(max_lat, max_lon)?;
+        state.db.get_sensors_in_bounds(min_lat, min_lon, max_lat, max_lon).await?
     } else if let (Some(lat), Some(lon)) = (params.lat, params.lon) {
+        validate_coordinates(lat, lon)?;
         // Get sensors within 50km of the point
-        state.db.get_sensors_near_point(lat, lon, 50.0).await
+        state.db.get_sensors_near_point(lat, lon, 50.0).await?
     } else {
-        state.db.get_all_sensors().await
+        state.db.get_all_sensors().await?
     };
 
-    match sensors_result {
-        Ok(sensors) => {
-            Json(json!({
-                "sensors": sensors,
-                "count": sensors.len(),
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }))
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let body = match format {
+        geojson::Format::Json => json!({
+            "sensors": sensors,
+            "count": sensors.len(),
+            "timestamp": timestamp
+        }),
+        geojson::Format::GeoJson => geojson::feature_collection(
+            sensors.iter().map(geojson::sensor_feature).collect(),
+            json!({ "timestamp": timestamp }),
+        ),
+    };
+
+    Ok(geojson::Negotiated { format, body })
+}
+
+#[axum::debug_handler]
+async fn sensor_detail_handler(
+    State(state): State<AppState>,
+    path: Result<Path<i32>, PathRejection>,
+    query: Result<Query<SensorDetailQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Path(sensor_id) = path?;
+    let Query(params) = query?;
+    let hours = params.hours.unwrap_or(24);
+    if !(1..=168).contains(&hours) {
+        return Err(ApiError::BadRequest(format!("hours must be between 1 and 168, got {}", hours)));
+    }
+
+    let Some(sensor) = state.db.get_sensor(sensor_id).await? else {
+        return Err(ApiError::NotFound(format!("Sensor {} not found", sensor_id)));
+    };
+    let end = chrono::Utc::now();
+    let recent_readings = state.db.get_sensor_readings(sensor_id, end - chrono::Duration::hours(hours), end, None).await?;
+    let latest_readings = state.db.get_latest_readings(&[sensor_id]).await?;
+
+    Ok(Json(json!({
+        "sensor": sensor,
+        "latest_readings": latest_readings,
+        "recent_readings": {
+            "hours": hours,
+            "readings": recent_readings,
+            "count": recent_readings.len()
+        },
+        "timestamp": end.to_rfc3339()
+    })))
+}
+
+async fn create_sensor_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Json<models::NewSensor>, JsonRejection>
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    authorize_admin(&state, &headers)?;
+    let Json(sensor) = body?;
+    sensor.validate().map_err(ApiError::BadRequest)?;
+
+    // With an external_id, posting the same station again updates it instead of adding another
+    let (sensor_id, outcome) = state
+        .db
+        .upsert_sensor(&sensor)
+        .await
+        .map_err(|e| sensor_write_error(e, &sensor))?;
+    if outcome == models::UpsertOutcome::Decommissioned {
+        return Err(ApiError::Conflict(format!(
+            "Sensor {} with external_id '{}' is decommissioned",
+            sensor_id,
+            sensor.external_id.unwrap_or_default()
+        )));
+    }
+    let status = if outcome == models::UpsertOutcome::Inserted { StatusCode::CREATED } else { StatusCode::OK };
+
+    Ok((
+        status,
+        Json(json!({
+            "sensor": state.db.get_sensor(sensor_id).await?,
+            "outcome": outcome,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        })),
+    ))
+}
+
+async fn update_sensor_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    path: Result<Path<i32>, PathRejection>,
+    body: Result<Json<models::SensorUpdate>, JsonRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize_admin(&state, &headers)?;
+    let Path(sensor_id) = path?;
+    let Json(update) = body?;
+
+    let Some(current) = state.db.get_sensor(sensor_id).await? else {
+        return Err(ApiError::NotFound(format!("Sensor {} not found", sensor_id)));
+    };
+    if current.decommissioned_at.is_some() {
+        return Err(ApiError::Conflict(format!("Sensor {} is decommissioned", sensor_id)));
+    }
+    let sensor = update.apply(&current);
+    sensor.validate().map_err(ApiError::BadRequest)?;
+
+    let updated = state
+        .db
+        .update_sensor(sensor_id, &sensor)
+        .await
+        .map_err(|e| sensor_write_error(e, &sensor))?;
+    if !updated {
+        return Err(ApiError::Conflict(format!("Sensor {} was decommissioned during the update", sensor_id)));
+    }
+
+    Ok(Json(json!({
+        "sensor": state.db.get_sensor(sensor_id).await?,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+/// Sensors are never deleted: their readings feed the daily indices and history views.
+/// Decommissioning stops ingestion and hides the sensor from listings, maps and risk lookups.
+async fn decommission_sensor_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    path: Result<Path<i32>, PathRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize_admin(&state, &headers)?;
+    let Path(sensor_id) = path?;
+
+    // Decommissioning twice is not an error
+    state.db.decommission_sensor(sensor_id).await?;
+    let Some(sensor) = state.db.get_sensor(sensor_id).await? else {
+        return Err(ApiError::NotFound(format!("Sensor {} not found", sensor_id)));
+    };
+
+    Ok(Json(json!({
+        "sensor": sensor,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+/// Sensors are unique by (data_source, name) among those not decommissioned, and by
+/// (data_source, external_id) whether decommissioned or not
+fn sensor_write_error(err: sqlx::Error, sensor: &models::NewSensor) -> ApiError {
+    let constraint = match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => db_err.constraint(),
+        _ => return err.into(),
+    };
+    match constraint {
+        Some("uq_sensors_external_id") => ApiError::Conflict(format!(
+            "A {} sensor with external_id '{}' already exists",
+            sensor.data_source,
+            sensor.external_id.as_deref().unwrap_or_default()
+        )),
+        Some("uq_sensors_data_source_name") => ApiError::Conflict(format!(
+            "An active {} sensor named '{}' already exists",
+            sensor.data_source, sensor.name
+        )),
+        _ => err.into(),
+    }
+}
+
+#[axum::debug_handler]
+async fn sensor_readings_handler(
+    State(state): State<AppState>,
+    path: Result<Path<i32>, PathRejection>,
+    query: Result<Query<ReadingsQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Path(sensor_id) = path?;
+    let Query(params) = query?;
+
+    // Default to the last 24 hours of history
+    let end = params.end.unwrap_or_else(chrono::Utc::now);
+    let start = params.start.unwrap_or(end - chrono::Duration::hours(24));
+    if start > end {
+        return Err(ApiError::BadRequest("start must be before end".to_string()));
+    }
+    if !state.db.sensor_exists(sensor_id).await? {
+        return Err(ApiError::NotFound(format!("Sensor {} not found", sensor_id)));
+    }
+
+    let readings = state.db.get_sensor_readings(sensor_id, start, end, params.parameter.as_deref()).await?;
+
+    Ok(Json(json!({
+        "sensor_id": sensor_id,
+        "start": start.to_rfc3339(),
+        "end": end.to_rfc3339(),
+        "readings": readings,
+        "count": readings.len(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+#[axum::debug_handler]
+async fn air_quality_forecast_handler(
+    State(state): State<AppState>,
+    query: Result<Query<ForecastQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Query(params) = query?;
+    let provider = state.air_quality.as_ref();
+
+    let (forecast, cached) = match (params.zip.as_deref(), params.lat, params.lon) {
+        (Some(zip_code), _, _) => {
+            state.forecast_cache.get_or_fetch(&format!("zip:{}", zip_code), params.date, || {
+                provider.forecast_by_zip(zip_code, params.date)
+            }).await?
         }
-        Err(e) => {
-            Json(json!({
-                "error": format!("Failed to fetch sensors: {}", e),
-                "sensors": [],
-                "count": 0,
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }))
+        (None, Some(lat), Some(lon)) => {
+            validate_coordinates(lat, lon)?;
+            state.forecast_cache.get_or_fetch(&format!("latlong:{:.4},{:.4}", lat, lon), params.date, || {
+                provider.forecast_by_coords(lat, lon, params.date)
+            }).await?
         }
-    }
+        _ => return Err(ApiError::BadRequest("Provide either zip or both lat and lon".to_string())),
+    };
+
+    Ok(Json(json!({
+        "location": forecast.location(),
+        "issued_on": forecast.issued_on,
+        "discussion": forecast.discussion,
+        "days": forecast.days,
+        "cached": cached,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
 }
 
 #[axum::debug_handler]
 async fn risk_prediction_handler(
     State(state): State<AppState>,
-    Query(params): Query<RiskQuery>
-) -> Json<serde_json::Value> {
+    headers: HeaderMap,
+    query: Result<Query<RiskQuery>, QueryRejection>
+) -> Result<geojson::Negotiated, ApiError> {
+    let Query(params) = query?;
+    let format = geojson::Format::negotiate(params.format, &headers);
+    validate_coordinates(params.lat, params.lon)?;
+    let nearby_sensors = state.db.get_sensors_near_point(params.lat, params.lon, 25.0).await?;
+    risk_prediction(&state, params, format, &nearby_sensors).await
+}
+
+/// Risk at a point, from what `nearby_sensors` stored or the live weather provider
+async fn risk_prediction(
+    state: &AppState,
+    params: RiskQuery,
+    format: geojson::Format,
+    nearby_sensors: &[models::SensorLocation],
+) -> Result<geojson::Negotiated, ApiError> {
     let lat = params.lat;
     let lon = params.lon;
+    let units = params.units.unwrap_or_default();
     
-    // Fetch current weather data for the location
-    match clients::get_weather_by_coords(lat, lon).await {
-        Ok(weather_data) => {
-            // Convert weather data to ML model format
-            let ml_weather_data = WeatherData {
-                temperature: weather_data.temperature as f32,
-                humidity: weather_data.humidity as f32,
-                wind_speed: weather_data.wind_speed as f32,
-                precipitation: 0.0, // Default - could be enhanced with historical data
-                drought_index: calculate_drought_index(&weather_data), // Calculated from current conditions
-            };
-            
-            // Make risk prediction
-            match state.risk_model.predict(&ml_weather_data) {
-                Ok(risk_probability) => {
-                    let risk_level = match risk_probability {
-                        p if p >= 0.8 => "EXTREME",
-                        p if p >= 0.6 => "HIGH", 
-                        p if p >= 0.4 => "MODERATE",
-                        p if p >= 0.2 => "LOW",
-                        _ => "MINIMAL"
-                    };
-                    
-                    Json(json!({
-                        "location": {
-                            "latitude": lat,
-                            "longitude": lon
-                        },
-                        "risk": {
-                            "probability": risk_probability,
-                            "level": risk_level,
-                            "description": get_risk_description(risk_level)
-                        },
-                        "weather_conditions": {
-                            "temperature": weather_data.temperature,
-                            "humidity": weather_data.humidity,
-                            "wind_speed": weather_data.wind_speed,
-                            "wind_direction": weather_data.wind_direction
-                        },
-                        "model_inputs": {
-                            "temperature": ml_weather_data.temperature,
-                            "humidity": ml_weather_data.humidity,
-                            "wind_speed": ml_weather_data.wind_speed,
-                            "precipitation": ml_weather_data.precipitation,
-                            "drought_index": ml_weather_data.drought_index
-                        },
-                        "timestamp": chrono::Utc::now().to_rfc3339()
-                    }))
-                }
-                Err(ml_err) => {
-                    Json(json!({
-                        "error": format!("Failed to make risk prediction: {}", ml_err),
-                        "location": {
-                            "latitude": lat,
-                            "longitude": lon
-                        },
-                        "timestamp": chrono::Utc::now().to_rfc3339()
-                    }))
-                }
-            }
+    // Use fresh weather stored for a nearby sensor, otherwise fetch current conditions
+    let now = chrono::Utc::now();
+    let cutoff = now - state.max_reading_age;
+    let weather_data = match ingestion::stored_weather(nearby_sensors, cutoff) {
+        Some(weather_data) => weather_data,
+        None => state.weather.current(lat, lon).await?,
+    };
+
+    // Trailing 7-day precipitation recorded by the ingestion worker at the nearest sensor
+    let precipitation_history = precipitation::trailing_history(&state.db, nearby_sensors, now).await?;
+    let recent_precipitation = precipitation::coverage(precipitation_history.as_ref(), now, UnitSystem::Imperial);
+    let kbdi = indices::daily::current_kbdi(&state.db, nearby_sensors, &state.indices, now.date_naive()).await?;
+
+    // Convert weather data to ML model format
+    let ml_weather_data = WeatherData::from_conditions(
+        weather_data.temperature,
+        weather_data.humidity as f32,
+        weather_data.wind_speed,
+        recent_precipitation.total as f32, // Inches
+        kbdi.state.model_input(),
+    );
+    
+    // Make risk prediction
+    let model = state.models.active();
+    let prediction = model.model().predict(&ml_weather_data).await?;
+    let risk_probability = prediction.probability;
+
+    // NFDRS indices for the same conditions, for comparison with the model
+    let heavy_fuels = indices::daily::current_heavy_fuels(&state.db, nearby_sensors, now.date_naive()).await?;
+    let nfdrs = indices::nfdrs::compute(
+        params.fuel_model.unwrap_or(state.indices.fuel_model),
+        &indices::nfdrs::NfdrsInputs {
+            temperature: weather_data.temperature,
+            humidity: weather_data.humidity as f64,
+            wind_speed: weather_data.wind_speed,
+            raining: weather_data.precipitation_last_hour.is_some_and(|p| p.millimeters() > 0.0),
+            hundred_hour: heavy_fuels.hundred_hour,
+            thousand_hour: heavy_fuels.thousand_hour,
+            herbaceous: state.indices.herbaceous_moisture,
+            woody: state.indices.woody_moisture,
+        },
+    );
+
+    let risk_level = get_risk_level(risk_probability);
+    
+    let body = json!({
+        "location": {
+            "latitude": lat,
+            "longitude": lon
+        },
+        "risk": {
+            "probability": risk_probability,
+            "level": risk_level,
+            "description": get_risk_description(risk_level)
+        },
+        "weather_conditions": weather_json(&weather_data, units),
+        "precipitation": precipitation::coverage(precipitation_history.as_ref(), now, units),
+        "drought_index": kbdi,
+        "nfdrs": {
+            "indices": nfdrs,
+            "heavy_fuels": heavy_fuels
+        },
+        "fire_weather": indices::FireWeather::from_conditions(&weather_data),
+        "model": model_json(&model.metadata),
+        "units": units.labels(),
+        // The model is always fed °F, %, mph, inches and a 0-100 index
+        "model_inputs": {
+            "temperature": ml_weather_data.temperature,
+            "humidity": ml_weather_data.humidity,
+            "wind_speed": ml_weather_data.wind_speed,
+            "precipitation": ml_weather_data.precipitation,
+            "drought_index": ml_weather_data.drought_index
+        },
+        // Inputs outside the model's training range that were scored at the nearest valid value
+        "clamped_inputs": prediction.clamped,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+
+    Ok(match format {
+        geojson::Format::Json => geojson::Negotiated { format, body },
+        geojson::Format::GeoJson => geojson::Negotiated { format, body: geojson::point_feature(lat, lon, body) },
+    })
+}
+
+#[axum::debug_handler]
+async fn risk_forecast_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    query: Result<Query<RiskQuery>, QueryRejection>
+) -> Result<geojson::Negotiated, ApiError> {
+    let Query(params) = query?;
+    let format = geojson::Format::negotiate(params.format, &headers);
+    validate_coordinates(params.lat, params.lon)?;
+    let nearby_sensors = state.db.get_sensors_near_point(params.lat, params.lon, 25.0).await?;
+    risk_forecast(&state, params, format, &nearby_sensors).await
+}
+
+/// Risk timeline at a point, starting from the rain and drought recorded by `nearby_sensors`
+async fn risk_forecast(
+    state: &AppState,
+    params: RiskQuery,
+    format: geojson::Format,
+    nearby_sensors: &[models::SensorLocation],
+) -> Result<geojson::Negotiated, ApiError> {
+    let (lat, lon) = (params.lat, params.lon);
+    let units = params.units.unwrap_or_default();
+
+    let forecast = state.weather.forecast(lat, lon).await?;
+    let now = chrono::Utc::now();
+    let precipitation_history = precipitation::trailing_history(&state.db, nearby_sensors, now).await?;
+    let kbdi = indices::daily::current_kbdi(&state.db, nearby_sensors, &state.indices, now.date_naive()).await?;
+
+    // Project KBDI through the forecast from each UTC day's expected high and rainfall
+    let mut forecast_days: std::collections::BTreeMap<chrono::NaiveDate, (Option<units::Temperature>, units::Precipitation)> =
+        std::collections::BTreeMap::new();
+    for step in &forecast.steps {
+        let (max_temperature, rainfall) = forecast_days
+            .entry(step.weather.observed_at.date_naive())
+            .or_insert((None, units::Precipitation::from_millimeters(0.0)));
+        if max_temperature.is_none_or(|t| step.weather.temperature > t) {
+            *max_temperature = Some(step.weather.temperature);
         }
-        Err(weather_err) => {
-            Json(json!({
-                "error": format!("Failed to fetch weather data: {}", weather_err),
-                "location": {
-                    "latitude": lat,
-                    "longitude": lon
-                },
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }))
+        *rainfall = units::Precipitation::from_millimeters(rainfall.millimeters() + step.precipitation.millimeters());
+    }
+    let projected_kbdi = indices::daily::project_kbdi(&kbdi, &forecast_days, state.indices.mean_annual_rainfall);
+
+    // Score every step with the same model even if it is swapped mid-request
+    let model = state.models.active();
+
+    let utc_offset = chrono::FixedOffset::east_opt(forecast.utc_offset_seconds)
+        .unwrap_or_else(|| chrono::FixedOffset::east_opt(0).unwrap());
+
+    let mut forecast_precipitation = 0.0; // mm expected from now until the step
+    let model_inputs: Vec<WeatherData> = forecast
+        .steps
+        .iter()
+        .map(|step| {
+            forecast_precipitation += step.precipitation.millimeters();
+
+            // The 7 days before each step: what has already fallen plus what is forecast until then
+            let window_start = step.weather.observed_at - chrono::Duration::days(precipitation::WINDOW_DAYS);
+            let recorded = precipitation_history
+                .as_ref()
+                .map_or(0.0, |history| history.total_between(window_start, now).millimeters());
+
+            WeatherData::from_conditions(
+                step.weather.temperature,
+                step.weather.humidity as f32,
+                step.weather.wind_speed,
+                units::Precipitation::from_millimeters(recorded + forecast_precipitation).inches() as f32,
+                projected_kbdi
+                    .get(&step.weather.observed_at.date_naive())
+                    .unwrap_or(&kbdi.state)
+                    .model_input(),
+            )
+        })
+        .collect();
+    // The whole timeline in one model run
+    let predictions = model.model().predict_batch(&model_inputs).await?;
+
+    let mut timeline = Vec::with_capacity(forecast.steps.len());
+    // Per local calendar day: (peak probability, time of the peak)
+    let mut daily_peaks: std::collections::BTreeMap<chrono::NaiveDate, (f32, chrono::DateTime<chrono::Utc>)> =
+        std::collections::BTreeMap::new();
+
+    for ((step, ml_weather_data), prediction) in forecast.steps.iter().zip(&model_inputs).zip(predictions) {
+        let risk_probability = prediction.probability;
+        let risk_level = get_risk_level(risk_probability);
+
+        let forecast_time = step.weather.observed_at;
+        let local_date = forecast_time.with_timezone(&utc_offset).date_naive();
+        let peak = daily_peaks.entry(local_date).or_insert((risk_probability, forecast_time));
+        if risk_probability > peak.0 {
+            *peak = (risk_probability, forecast_time);
+        }
+
+        timeline.push(json!({
+            "time": forecast_time.to_rfc3339(),
+            "probability": risk_probability,
+            "level": risk_level,
+            "weather": weather_json(&step.weather, units),
+            "precipitation": step.precipitation.in_units(units),
+            "precipitation_probability": step.precipitation_probability,
+            "drought_index": ml_weather_data.drought_index,
+            "fire_weather": indices::FireWeather::from_conditions(&step.weather),
+            "clamped_inputs": prediction.clamped
+        }));
+    }
+
+    let daily: Vec<serde_json::Value> = daily_peaks
+        .into_iter()
+        .map(|(date, (probability, time))| json!({
+            "date": date,
+            "max_probability": probability,
+            "level": get_risk_level(probability),
+            "peak_time": time.to_rfc3339()
+        }))
+        .collect();
+
+    let body = json!({
+        "location": {
+            "latitude": lat,
+            "longitude": lon
+        },
+        "units": units.labels(),
+        "utc_offset_seconds": forecast.utc_offset_seconds,
+        "recorded_precipitation": precipitation::coverage(precipitation_history.as_ref(), now, units),
+        "drought_index": kbdi,
+        "model": model_json(&model.metadata),
+        "daily": daily,
+        "timeline": timeline,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+
+    Ok(match format {
+        geojson::Format::Json => geojson::Negotiated { format, body },
+        geojson::Format::GeoJson => geojson::Negotiated { format, body: geojson::point_feature(lat, lon, body) },
+    })
+}
+
+#[axum::debug_handler]
+async fn risk_grid_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    query: Result<Query<GridQuery>, QueryRejection>
+) -> Result<geojson::Negotiated, ApiError> {
+    let Query(params) = query?;
+    let format = geojson::Format::negotiate(params.format, &headers);
+    validate_coordinates(params.min_lat, params.min_lon)?;
+    validate_coordinates(params.max_lat, params.max_lon)?;
+    let grid = grid::GridSpec::new(
+        params.min_lat,
+        params.min_lon,
+        params.max_lat,
+        params.max_lon,
+        params.resolution.unwrap_or(grid::DEFAULT_RESOLUTION),
+    )
+    .map_err(ApiError::BadRequest)?;
+
+    let (min_lat, min_lon, max_lat, max_lon) = grid.sample_bounds();
+    let sensors = state.db.get_sensors_in_bounds(min_lat, min_lon, max_lat, max_lon).await?;
+    risk_grid(&state, &grid, &sensors, format).await
+}
+
+/// Score every cell of `grid` from the sensors in and around it
+async fn risk_grid(
+    state: &AppState,
+    grid: &grid::GridSpec,
+    sensors: &[models::SensorLocation],
+    format: geojson::Format,
+) -> Result<geojson::Negotiated, ApiError> {
+    let now = chrono::Utc::now();
+    let (samples, sample_source) = grid_samples(state, grid, sensors, now).await?;
+
+    // Interpolate every cell, then score the whole grid in one batch
+    let cells: Vec<WeatherData> = grid
+        .cells()
+        .filter_map(|(_, _, lat, lon)| grid::interpolate(&samples, lat, lon))
+        .collect();
+    if cells.len() != grid.cell_count() {
+        return Err(ApiError::NotFound("No weather available to interpolate over this area".to_string()));
+    }
+    let model = state.models.active();
+    let predictions = model.model().predict_batch(&cells).await?;
+    let clamped_cells = predictions.iter().filter(|p| !p.clamped.is_empty()).count();
+
+    let samples_json = json!({
+        "source": sample_source,
+        "count": samples.len()
+    });
+
+    let body = match format {
+        geojson::Format::Json => json!({
+            "grid": grid,
+            "cell_order": "row-major from the south-west corner",
+            "probabilities": predictions.iter().map(|p| p.probability).collect::<Vec<f32>>(),
+            "samples": samples_json,
+            "clamped_cells": clamped_cells,
+            "model": model_json(&model.metadata),
+            "timestamp": now.to_rfc3339()
+        }),
+        geojson::Format::GeoJson => {
+            let features: Vec<serde_json::Value> = grid
+                .cells()
+                .zip(&predictions)
+                .map(|((row, col, _, _), prediction)| {
+                    let (south, west, north, east) = grid.cell_bounds(row, col);
+                    geojson::feature(
+                        geojson::bounds_polygon(south, west, north, east),
+                        json!({
+                            "row": row,
+                            "col": col,
+                            "probability": prediction.probability,
+                            "level": get_risk_level(prediction.probability)
+                        }),
+                    )
+                })
+                .collect();
+            geojson::feature_collection(
+                features,
+                json!({
+                    "grid": grid,
+                    "samples": samples_json,
+                    "clamped_cells": clamped_cells,
+                    "model": model_json(&model.metadata),
+                    "timestamp": now.to_rfc3339()
+                }),
+            )
         }
+    };
+
+    Ok(geojson::Negotiated { format, body })
+}
+
+/// Model features at known locations around a grid: every sensor with fresh observations,
+/// or forecast-provider weather at the grid's corners and center when none has any
+async fn grid_samples(
+    state: &AppState,
+    grid: &grid::GridSpec,
+    sensors: &[models::SensorLocation],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(Vec<grid::Sample>, &'static str), ApiError> {
+    let today = now.date_naive();
+
+    let cutoff = now - state.max_reading_age;
+    let samples = risk_layer::observed_samples(&state.db, &state.indices, sensors, cutoff, now).await?;
+    if !samples.is_empty() {
+        return Ok((samples, "observations"));
+    }
+
+    // No fresh observations: current conditions from the weather provider, with the region's
+    // recorded rain and drought applied uniformly
+    let mut requests = tokio::task::JoinSet::new();
+    for (lat, lon) in grid.anchor_points() {
+        let weather = state.weather.clone();
+        requests.spawn(async move { (lat, lon, weather.current(lat, lon).await) });
+    }
+    let history = precipitation::trailing_history(&state.db, sensors, now).await?;
+    let rainfall = precipitation::coverage(history.as_ref(), now, UnitSystem::Imperial).total as f32;
+    let drought_index = indices::daily::current_kbdi(&state.db, sensors, &state.indices, today)
+        .await?
+        .state
+        .model_input();
+
+    let mut samples = Vec::new();
+    let mut last_error = None;
+    while let Some(result) = requests.join_next().await {
+        match result {
+            Ok((latitude, longitude, Ok(weather))) => samples.push(grid::Sample {
+                latitude,
+                longitude,
+                features: WeatherData::from_conditions(
+                    weather.temperature,
+                    weather.humidity as f32,
+                    weather.wind_speed,
+                    rainfall,
+                    drought_index,
+                ),
+            }),
+            Ok((_, _, Err(e))) => last_error = Some(ApiError::from(e)),
+            Err(e) => last_error = Some(ApiError::UpstreamUnavailable(format!("Weather request failed: {}", e))),
+        }
+    }
+
+    match (samples.is_empty(), last_error) {
+        (true, Some(e)) => Err(e),
+        _ => Ok((samples, "weather_api")),
+    }
+}
+
+async fn tile_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    path: Result<Path<(String, u32, u32, String)>, PathRejection>
+) -> Result<Response, ApiError> {
+    let Path((layer, z, x, y)) = path?;
+    let layer: tiles::TileLayer = layer.parse().map_err(ApiError::NotFound)?;
+    let coord = tiles::TileCoord::parse(z, x, &y).map_err(ApiError::BadRequest)?;
+
+    let tile = state.tile_cache.get_or_render(&state.db, layer, coord).await?;
+    let cache_control = format!("public, max-age={}", state.tile_cache.ttl().as_secs());
+
+    let revalidated = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| tile.matches(value));
+    if revalidated {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, tile.etag.clone()), (header::CACHE_CONTROL, cache_control)],
+        )
+            .into_response());
+    }
+
+    // An empty tile is a valid tile; map clients expect 200 with no features rather than 404
+    Ok((
+        [
+            (header::CONTENT_TYPE, tiles::CONTENT_TYPE.to_string()),
+            (header::ETAG, tile.etag.clone()),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        tile.data.clone(),
+    )
+        .into_response())
+}
+
+async fn fwi_handler(
+    State(state): State<AppState>,
+    query: Result<Query<FwiQuery>, QueryRejection>
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Query(params) = query?;
+    validate_coordinates(params.lat, params.lon)?;
+    let days = params.days.unwrap_or(7);
+    if !(1..=90).contains(&days) {
+        return Err(ApiError::BadRequest(format!("days must be between 1 and 90, got {}", days)));
+    }
+
+    let nearby_sensors = state.db.get_sensors_near_point(params.lat, params.lon, 25.0).await?;
+    let since = chrono::Utc::now().date_naive() - chrono::Duration::days(days);
+    let Some((sensor_id, history)) = indices::daily::fwi_history(&state.db, &nearby_sensors, since).await? else {
+        return Err(ApiError::NotFound(format!(
+            "No Fire Weather Index has been computed within 25 km of ({}, {})",
+            params.lat, params.lon
+        )));
+    };
+
+    Ok(Json(json!({
+        "location": {
+            "latitude": params.lat,
+            "longitude": params.lon
+        },
+        "sensor_id": sensor_id,
+        "latest": history.first(),
+        "history": history,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+async fn ingestion_runs_handler(State(state): State<AppState>) -> Result<Json<serde_json::Value>, ApiError> {
+    let runs = state.db.get_recent_ingestion_runs(20).await?;
+
+    Ok(Json(json!({
+        "runs": runs,
+        "count": runs.len(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+#[derive(Deserialize, Default)]
+struct ReloadModelRequest {
+    version: Option<String>, // Defaults to whatever the registry's ACTIVE file names
+}
+
+fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(expected) = state.admin_token.as_deref() else {
+        return Err(ApiError::Unauthorized("Admin endpoints are disabled; set ADMIN_TOKEN to enable them".to_string()));
+    };
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Compared in constant time so response timing doesn't reveal how much of a guess was right
+    let matches = provided.is_some_and(|provided| bool::from(provided.as_bytes().ct_eq(expected.as_bytes())));
+    if !matches {
+        return Err(ApiError::Unauthorized("Missing or invalid admin token".to_string()));
     }
+    Ok(())
 }
 
-fn calculate_drought_index(weather_data: &clients::weather::WeatherData) -> f32 {
-    // Simple drought index calculation based on temperature and humidity
-    // Higher temperature + lower humidity = higher drought index
-    let temp_factor = (weather_data.temperature - 32.0) / 100.0; // Normalize from Fahrenheit
-    let humidity_factor = 1.0 - (weather_data.humidity as f64 / 100.0);
-    let drought_index = ((temp_factor + humidity_factor) * 50.0).max(0.0).min(100.0);
-    drought_index as f32
+async fn models_handler(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize_admin(&state, &headers)?;
+    let active = state.models.active();
+    let versions = state.models.versions()?;
+
+    Ok(Json(json!({
+        "active": active.metadata,
+        "loaded_at": active.loaded_at.to_rfc3339(),
+        "versions": versions,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+async fn reload_model_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Json<ReloadModelRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize_admin(&state, &headers)?;
+    // A request without a body reloads the version ACTIVE names; any body must be valid JSON
+    let empty_body = headers
+        .get(header::CONTENT_LENGTH)
+        .map_or(!headers.contains_key(header::TRANSFER_ENCODING), |length| length == "0");
+    let Json(request) = match body {
+        Ok(body) => body,
+        Err(_) if empty_body => Json(ReloadModelRequest::default()),
+        Err(rejection) => return Err(rejection.into()),
+    };
+    let previous = state.models.active().metadata.version.clone();
+
+    // Building an ONNX session reads the file and can take a while
+    let models = state.models.clone();
+    let loaded = tokio::task::spawn_blocking(move || models.reload(request.version.as_deref()))
+        .await
+        .map_err(|e| ApiError::ModelFailure(format!("Model reload task failed: {}", e)))??;
+    println!("Reloaded risk model {} (was {})", loaded.metadata.version, previous);
+
+    Ok(Json(json!({
+        "previous_version": previous,
+        "active": loaded.metadata,
+        "loaded_at": loaded.loaded_at.to_rfc3339(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })))
+}
+
+fn model_json(metadata: &ModelMetadata) -> serde_json::Value {
+    json!({
+        "version": metadata.version,
+        "trained_at": metadata.trained_at
+    })
+}
+
+fn validate_coordinates(lat: f64, lon: f64) -> Result<(), ApiError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::BadRequest(format!(
+            "Coordinates out of range: lat must be within [-90, 90] and lon within [-180, 180], got ({}, {})",
+            lat, lon
+        )));
+    }
+    Ok(())
+}
+
+fn weather_json(weather_data: &clients::WeatherData, units: UnitSystem) -> serde_json::Value {
+    json!({
+        "temperature": weather_data.temperature.in_units(units),
+        "humidity": weather_data.humidity,
+        "wind_speed": weather_data.wind_speed.in_units(units),
+        "wind_direction": weather_data.wind_direction
+    })
+}
+
+fn get_risk_level(probability: f32) -> &'static str {
+    match probability {
+        p if p >= 0.8 => "EXTREME",
+        p if p >= 0.6 => "HIGH",
+        p if p >= 0.4 => "MODERATE",
+        p if p >= 0.2 => "LOW",
+        _ => "MINIMAL"
+    }
 }
 
 fn get_risk_description(level: &str) -> String {
@@ -435,4 +1280,342 @@ fn get_risk_description(level: &str) -> String {
         "MINIMAL" => "Minimal fire danger. Conditions are favorable for fire safety.".to_string(),
         _ => "Fire risk assessment unavailable.".to_string()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use clients::{AirQualityForecast, AirQualityIndex, ProviderError, WeatherData, WeatherForecast};
+
+    fn fake_failure(provider: &'static str, message: &str) -> ProviderError {
+        ProviderError::Request { provider, message: message.to_string() }
+    }
+
+    struct FakeAirQuality(Result<AirQualityIndex, String>);
+
+    #[async_trait]
+    impl AirQualityProvider for FakeAirQuality {
+        async fn current_by_zip(&self, _zip_code: &str) -> Result<AirQualityIndex, ProviderError> {
+            self.0.clone().map_err(|e| fake_failure("AirNow", &e))
+        }
+
+        async fn current_by_coords(&self, _lat: f64, _lon: f64) -> Result<AirQualityIndex, ProviderError> {
+            self.0.clone().map_err(|e| fake_failure("AirNow", &e))
+        }
+
+        async fn forecast_by_zip(&self, _zip_code: &str, _date: Option<chrono::NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+            Err(ProviderError::NoData("No forecast".to_string()))
+        }
+
+        async fn forecast_by_coords(&self, _lat: f64, _lon: f64, _date: Option<chrono::NaiveDate>) -> Result<AirQualityForecast, ProviderError> {
+            Err(ProviderError::NoData("No forecast".to_string()))
+        }
+    }
+
+    struct FakeWeather(Result<WeatherData, String>);
+
+    #[async_trait]
+    impl WeatherProvider for FakeWeather {
+        async fn current(&self, _lat: f64, _lon: f64) -> Result<WeatherData, ProviderError> {
+            self.0.clone().map_err(|e| fake_failure("OpenWeatherMap", &e))
+        }
+
+        async fn forecast(&self, _lat: f64, _lon: f64) -> Result<WeatherForecast, ProviderError> {
+            Err(ProviderError::NoData("No forecast".to_string()))
+        }
+    }
+
+    fn sample_air_quality() -> AirQualityIndex {
+        AirQualityIndex {
+            aqi: 42,
+            category: "Good".to_string(),
+            observed_at: None,
+            reporting_area: Some("Bend".to_string()),
+            state_code: Some("OR".to_string()),
+            dominant_pollutant: None,
+            pollutants: Vec::new(),
+        }
+    }
+
+    fn sample_weather() -> WeatherData {
+        WeatherData {
+            temperature: units::Temperature::from_celsius(21.5),
+            humidity: 35,
+            wind_speed: units::Speed::from_meters_per_second(4.2),
+            wind_direction: 270.0,
+            precipitation_last_hour: None,
+            observed_at: chrono::Utc::now(),
+        }
+    }
+
+    fn test_state(air_quality: Arc<dyn AirQualityProvider>, weather: Arc<dyn WeatherProvider>) -> AppState {
+        // Nothing listens on port 1, so database calls fail fast
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .connect_lazy("postgres://helios@127.0.0.1:1/helios")
+            .expect("Invalid test database URL");
+
+        AppState {
+            db: Arc::new(Database::new(pool)),
+            models: Arc::new(
+                ModelRegistry::open(
+                    concat!(env!("CARGO_MANIFEST_DIR"), "/models"),
+                    ml::registry::LoadOptions {
+                        out_of_range: ml::OutOfRangePolicy::Clamp,
+                        sessions: 1,
+                    },
+                )
+                .expect("Failed to load model"),
+            ),
+            admin_token: Some("test-token".to_string()),
+            air_quality,
+            weather,
+            forecast_cache: Arc::new(ForecastCache::new(std::time::Duration::from_secs(60))),
+            tile_cache: Arc::new(tiles::TileCache::new(std::time::Duration::from_secs(60), 100)),
+            indices: IndicesConfig {
+                enabled: false,
+                kbdi_initial: 200.0,
+                mean_annual_rainfall: units::Precipitation::from_inches(20.0),
+                fuel_model: indices::nfdrs::FuelModel::G,
+                herbaceous_moisture: 60.0,
+                woody_moisture: 90.0,
+            },
+            max_reading_age: chrono::Duration::minutes(30),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_environmental_data_uses_injected_providers() {
+        let state = test_state(
+            Arc::new(FakeAirQuality(Ok(sample_air_quality()))),
+            Arc::new(FakeWeather(Ok(sample_weather()))),
+        );
+
+        let Json(body) = environmental_data(&state, 44.1, -121.7, UnitSystem::Metric, Vec::new())
+            .await.expect("Request should succeed");
+
+        assert_eq!(body["air_quality"]["aqi"], 42);
+        assert_eq!(body["weather"]["humidity"], 35);
+        assert_eq!(body["data_sources"]["weather"], "live");
+        assert!(body.get("errors").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_environmental_data_reports_provider_failure() {
+        let state = test_state(
+            Arc::new(FakeAirQuality(Err("AirNow is down".to_string()))),
+            Arc::new(FakeWeather(Ok(sample_weather()))),
+        );
+
+        let Json(body) = environmental_data(&state, 44.1292, -121.7689, UnitSystem::Metric, Vec::new())
+            .await.expect("Partial data should still be served");
+
+        assert_eq!(body["air_quality"]["category"], "Data Unavailable");
+        assert_eq!(body["weather"]["temperature"], 21.5);
+        assert_eq!(body["errors"][0]["code"], "UPSTREAM_UNAVAILABLE");
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("AirNow is down"));
+    }
+
+    #[tokio::test]
+    async fn test_environmental_data_fails_when_all_providers_fail() {
+        let state = test_state(
+            Arc::new(FakeAirQuality(Err("AirNow is down".to_string()))),
+            Arc::new(FakeWeather(Err("OpenWeatherMap is down".to_string()))),
+        );
+
+        let err = environmental_data(&state, 44.1292, -121.7689, UnitSystem::Metric, Vec::new())
+            .await.expect_err("Request should fail");
+
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_GATEWAY);
+        assert_eq!(err.code(), "UPSTREAM_UNAVAILABLE");
+    }
+
+    fn fixture_state() -> AppState {
+        let provider = Arc::new(clients::FixtureProvider::new(clients::FixtureStore::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"),
+        )));
+        test_state(provider.clone(), provider)
+    }
+
+    #[tokio::test]
+    async fn test_environmental_data_replays_fixtures() {
+        let Json(body) = environmental_data(&fixture_state(), 44.13, -121.77, UnitSystem::Metric, Vec::new())
+            .await.expect("Request should succeed");
+
+        assert_eq!(body["air_quality"]["aqi"], 112);
+        assert_eq!(body["air_quality"]["dominant_pollutant"], "PM2.5");
+        assert_eq!(body["air_quality"]["pollutants"].as_array().unwrap().len(), 3);
+        assert_eq!(body["air_quality"]["location"], "Klamath Falls, OR");
+        assert_eq!(body["weather"]["temperature"], 31.4);
+        assert_eq!(body["units"]["temperature"], "°C");
+        assert_eq!(body["weather"]["humidity"], 14);
+        assert!(body.get("errors").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_environmental_data_reports_bad_queries_and_database_failures() {
+        let uri: axum::http::Uri = "/environmental-data?lat=north".parse().unwrap();
+        let err = environmental_data_handler(State(fixture_state()), Query::try_from_uri(&uri))
+            .await.expect_err("A malformed query should be rejected");
+        assert_eq!(err.code(), "BAD_REQUEST");
+
+        // The test database is unreachable; that is an error, not a point without sensors
+        let err = environmental_data_handler(
+            State(fixture_state()),
+            Ok(Query(LocationQuery { lat: Some(44.13), lon: Some(-121.77), units: None })),
+        ).await.expect_err("Request should fail");
+        assert_eq!(err.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_status_replays_fixtures() {
+        let Json(body) = status_handler(
+            State(fixture_state()),
+            Ok(Path("97601".to_string())),
+            Ok(Query(UnitsQuery { units: Some(UnitSystem::Imperial) })),
+        ).await.expect("Request should succeed");
+
+        assert_eq!(body["aqi"], 112);
+        assert_eq!(body["dominant_pollutant"], "PM2.5");
+        assert_eq!(body["pollutants"][0]["parameter"], "O3");
+        assert_eq!(body["units"]["wind_speed"], "mph");
+        let temperature = body["temperature"].as_f64().unwrap();
+        assert!((temperature - 88.52).abs() < 1e-9, "31.4°C should be 88.52°F, got {}", temperature);
+    }
+
+    #[tokio::test]
+    async fn test_air_quality_forecast_replays_fixtures() {
+        let state = fixture_state();
+        let query = || Ok(Query(ForecastQuery { zip: Some("97601".to_string()), lat: None, lon: None, date: None }));
+
+        let Json(body) = air_quality_forecast_handler(State(state.clone()), query())
+            .await.expect("Request should succeed");
+        assert_eq!(body["location"], "Klamath Falls, OR");
+        assert_eq!(body["days"].as_array().unwrap().len(), 3);
+        assert_eq!(body["days"][0]["action_day"], true);
+        assert_eq!(body["cached"], false);
+
+        let Json(body) = air_quality_forecast_handler(State(state), query())
+            .await.expect("Request should succeed");
+        assert_eq!(body["cached"], true);
+    }
+
+    fn risk_query() -> RiskQuery {
+        RiskQuery { lat: 44.13, lon: -121.77, units: None, fuel_model: None, format: None }
+    }
+
+    #[tokio::test]
+    async fn test_risk_forecast_scores_every_step() {
+        // No sensors around, so the timeline starts without recorded rain or tracked drought
+        let body = risk_forecast(&fixture_state(), risk_query(), geojson::Format::Json, &[])
+            .await.expect("Request should succeed").body;
+
+        // Metric, like every other endpoint, unless asked otherwise
+        assert_eq!(body["units"]["temperature"], "°C");
+        let timeline = body["timeline"].as_array().unwrap();
+        assert_eq!(timeline.len(), 40);
+        assert_eq!(timeline[0]["time"], "2025-08-14T21:00:00+00:00");
+        assert_eq!(timeline[0]["weather"]["temperature"], 32.6);
+        assert!(timeline[0]["fire_weather"]["fosberg"].as_f64().unwrap() > 0.0);
+        assert_eq!(body["model"]["version"], "1.0.0");
+
+        // Days are local (PDT): the fixture starts mid-afternoon on the 14th
+        let daily = body["daily"].as_array().unwrap();
+        assert_eq!(daily[0]["date"], "2025-08-14");
+        assert_eq!(daily.len(), 6);
+
+        // The same timeline as a point feature for GIS tools
+        let response = risk_forecast(&fixture_state(), risk_query(), geojson::Format::GeoJson, &[])
+            .await.expect("Request should succeed");
+        assert_eq!(response.format, geojson::Format::GeoJson);
+        assert_eq!(response.body["type"], "Feature");
+        assert_eq!(response.body["geometry"]["coordinates"], json!([-121.77, 44.13]));
+        assert_eq!(response.body["properties"]["timeline"].as_array().unwrap().len(), 40);
+
+        // The test database is unreachable; neither risk endpoint scores as if no sensor were nearby
+        let err = risk_forecast_handler(State(fixture_state()), HeaderMap::new(), Ok(Query(risk_query())))
+            .await.expect_err("A database failure should fail the request");
+        assert_eq!(err.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        let err = risk_prediction_handler(State(fixture_state()), HeaderMap::new(), Ok(Query(risk_query())))
+            .await.expect_err("A database failure should fail the request");
+        assert_eq!(err.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_risk_grid_scores_every_cell() {
+        let grid = grid::GridSpec::new(44.0, -121.9, 44.2, -121.6, 0.1).unwrap();
+
+        // No sensors around, so the grid is sampled from the weather provider
+        let body = risk_grid(&fixture_state(), &grid, &[], geojson::Format::Json)
+            .await.expect("Request should succeed").body;
+        assert_eq!(body["grid"]["rows"], 2);
+        assert_eq!(body["grid"]["cols"], 3);
+        assert_eq!(body["probabilities"].as_array().unwrap().len(), 6);
+        assert_eq!(body["samples"]["source"], "weather_api");
+
+        let body = risk_grid(&fixture_state(), &grid, &[], geojson::Format::GeoJson)
+            .await.expect("Request should succeed").body;
+        assert_eq!(body["type"], "FeatureCollection");
+        assert_eq!(body["features"].as_array().unwrap().len(), 6);
+        assert_eq!(body["features"][0]["geometry"]["coordinates"][0][0][0], -121.9);
+
+        // The test database is unreachable, which is not the same as an area without sensors
+        let query = GridQuery { min_lat: 44.0, min_lon: -121.9, max_lat: 44.2, max_lon: -121.6, resolution: Some(0.1), format: None };
+        let err = risk_grid_handler(State(fixture_state()), HeaderMap::new(), Ok(Query(query)))
+            .await.expect_err("A database failure should fail the request");
+        assert_eq!(err.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_create_sensor_is_validated_before_it_reaches_the_database() {
+        let sensor = |latitude| models::NewSensor {
+            name: "Bend-NE 27th Street".to_string(),
+            data_source: "AirNow".to_string(),
+            latitude,
+            longitude: -121.2767,
+            external_id: Some("410170004".to_string()),
+            elevation: None,
+            agency: None,
+            parameters: vec!["PM2.5".to_string()],
+            active: true,
+        };
+
+        let err = create_sensor_handler(State(fixture_state()), HeaderMap::new(), Ok(Json(sensor(44.06))))
+            .await.expect_err("Creating a sensor without a token should fail");
+        assert!(matches!(err, ApiError::Unauthorized(_)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer test-token".parse().unwrap());
+        let err = create_sensor_handler(State(fixture_state()), headers, Ok(Json(sensor(94.06))))
+            .await.expect_err("Out of range latitudes should be rejected");
+        assert!(matches!(err, ApiError::BadRequest(ref message) if message.contains("latitude")), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_model_reload_requires_the_admin_token() {
+        let state = fixture_state();
+        let no_body = || Err(JsonRejection::from(axum::extract::rejection::MissingJsonContentType::default()));
+
+        let err = reload_model_handler(State(state.clone()), HeaderMap::new(), no_body())
+            .await.expect_err("Reload without a token should fail");
+        assert!(matches!(err, ApiError::Unauthorized(_)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer test-token".parse().unwrap());
+        let Json(body) = reload_model_handler(State(state.clone()), headers.clone(), no_body())
+            .await.expect("Reload should succeed");
+        assert_eq!(body["active"]["version"], "1.0.0");
+
+        let request = ReloadModelRequest { version: Some("0.0.0".to_string()) };
+        let err = reload_model_handler(State(state.clone()), headers.clone(), Ok(Json(request)))
+            .await.expect_err("Unknown versions should be rejected");
+        assert!(matches!(err, ApiError::NotFound(_)));
+
+        // A body that failed to parse is not a request for the ACTIVE version
+        let mut with_body = headers;
+        with_body.insert(header::CONTENT_LENGTH, "16".parse().unwrap());
+        let err = reload_model_handler(State(state), with_body, no_body())
+            .await.expect_err("An unparseable body should be rejected");
+        assert!(matches!(err, ApiError::BadRequest(_)));
+    }
+}
@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[error("Failed to load model: {0}")]
+    Load(#[source] ort::Error),
+    #[error("Inference failed: {0}")]
+    Inference(#[source] ort::Error),
+    #[error("Model output '{0}' is missing")]
+    MissingOutput(String),
+    #[error("Model does not match its feature schema: {0}")]
+    Schema(String),
+    #[error("{0}")]
+    OutOfRange(String),
+    #[error("Model session lock was poisoned")]
+    Poisoned,
+    #[error("Inference task failed: {0}")]
+    Aborted(String), // The blocking task running the model panicked or was cancelled
+    #[error("Model version '{0}' is not in the registry")]
+    UnknownVersion(String),
+    #[error("{0}")]
+    Registry(String),
+}
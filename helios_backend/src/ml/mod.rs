@@ -1,3 +1,9 @@
+pub mod error;
+pub mod registry;
 pub mod risk_model;
+pub mod schema;
 
+pub use error::ModelError;
+pub use registry::{ModelMetadata, ModelRegistry};
 pub use risk_model::{RiskModel, WeatherData};
+pub use schema::OutOfRangePolicy;
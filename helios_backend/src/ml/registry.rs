@@ -0,0 +1,252 @@
+// Versioned model registry. Each version lives in its own directory:
+//
+//   models/
+//     ACTIVE              <- the version to serve, e.g. "1.0.0"
+//     1.0.0/model.onnx
+//     1.0.0/metadata.json
+//
+// The active model can be swapped while the server runs; requests already holding the
+// previous model finish with it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use super::schema::{FeatureSchema, FeatureSpec, OutOfRangePolicy};
+use super::{ModelError, RiskModel};
+
+pub const ACTIVE_FILE: &str = "ACTIVE";
+pub const MODEL_FILE: &str = "model.onnx";
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// Contents of a version's `metadata.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    pub version: String,
+    pub trained_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub feature_schema: Vec<FeatureSpec>,
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>, // e.g. accuracy, roc_auc on the held-out set
+}
+
+pub struct LoadedModel {
+    pub metadata: ModelMetadata,
+    pub loaded_at: DateTime<Utc>,
+    model: Arc<RiskModel>,
+}
+
+impl LoadedModel {
+    pub fn model(&self) -> &Arc<RiskModel> {
+        &self.model
+    }
+}
+
+/// How every version in a registry is loaded
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    pub out_of_range: OutOfRangePolicy,
+    pub sessions: usize, // Concurrent inference sessions per model
+}
+
+pub struct ModelRegistry {
+    dir: PathBuf,
+    options: LoadOptions,
+    active: RwLock<Arc<LoadedModel>>,
+}
+
+impl ModelRegistry {
+    /// Load the version named in the registry's `ACTIVE` file
+    pub fn open(dir: impl Into<PathBuf>, options: LoadOptions) -> Result<Self, ModelError> {
+        let dir = dir.into();
+        let version = read_active_version(&dir)?;
+        let loaded = load_version(&dir, &version, options)?;
+        Ok(ModelRegistry {
+            dir,
+            options,
+            active: RwLock::new(Arc::new(loaded)),
+        })
+    }
+
+    /// The model currently serving predictions
+    pub fn active(&self) -> Arc<LoadedModel> {
+        self.active.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Load `version` (or whatever `ACTIVE` now names) and swap it in. Naming a version also
+    /// records it in `ACTIVE` so it survives a restart. The current model keeps serving if
+    /// the new one fails to load.
+    pub fn reload(&self, version: Option<&str>) -> Result<Arc<LoadedModel>, ModelError> {
+        let recorded = read_active_version(&self.dir).ok();
+        let version = match version {
+            Some(version) => version.to_string(),
+            None => read_active_version(&self.dir)?,
+        };
+        let loaded = Arc::new(load_version(&self.dir, &version, self.options)?);
+
+        if recorded.as_deref() != Some(version.as_str()) {
+            fs::write(self.dir.join(ACTIVE_FILE), format!("{}\n", version))
+                .map_err(|e| ModelError::Registry(format!("Failed to record active version: {}", e)))?;
+        }
+
+        *self.active.write().unwrap_or_else(|e| e.into_inner()) = loaded.clone();
+        Ok(loaded)
+    }
+
+    /// Metadata for every version in the registry, oldest version name first
+    pub fn versions(&self) -> Result<Vec<ModelMetadata>, ModelError> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| ModelError::Registry(format!("Failed to read {}: {}", self.dir.display(), e)))?;
+
+        let mut versions: Vec<ModelMetadata> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join(METADATA_FILE).is_file())
+            .filter_map(|entry| read_metadata(&entry.path()).ok())
+            .collect();
+        versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+        Ok(versions)
+    }
+}
+
+/// Reload whatever `ACTIVE` names each time the process receives SIGHUP
+#[cfg(unix)]
+pub fn reload_on_hangup(registry: Arc<ModelRegistry>) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    Ok(tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            let registry = registry.clone();
+            match tokio::task::spawn_blocking(move || registry.reload(None)).await {
+                Ok(Ok(loaded)) => println!("Reloaded risk model {}", loaded.metadata.version),
+                Ok(Err(e)) => eprintln!("Failed to reload risk model: {}", e),
+                Err(e) => eprintln!("Risk model reload task failed: {}", e),
+            }
+        }
+    }))
+}
+
+fn read_active_version(dir: &Path) -> Result<String, ModelError> {
+    let path = dir.join(ACTIVE_FILE);
+    let version = fs::read_to_string(&path)
+        .map_err(|e| ModelError::Registry(format!("Failed to read {}: {}", path.display(), e)))?;
+    let version = version.trim();
+    if version.is_empty() {
+        return Err(ModelError::Registry(format!("{} is empty", path.display())));
+    }
+    Ok(version.to_string())
+}
+
+/// Semantic version order: numeric components compare as numbers, so 1.10.0 follows 1.9.0, and
+/// a pre-release (1.1.0-rc.1) precedes its release. Names that are not versions sort as text.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parse(version: &str) -> Option<(Vec<u64>, Option<&str>)> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let version = version.split('+').next().unwrap_or_default(); // Build metadata doesn't order
+        let (release, pre_release) = match version.split_once('-') {
+            Some((release, pre_release)) => (release, Some(pre_release)),
+            None => (version, None),
+        };
+        let numbers = release.split('.').map(|n| n.parse().ok()).collect::<Option<Vec<u64>>>()?;
+        Some((numbers, pre_release))
+    }
+
+    match (parse(a), parse(b)) {
+        (Some((a_numbers, a_pre)), Some((b_numbers, b_pre))) => a_numbers.cmp(&b_numbers).then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a_pre), Some(b_pre)) => a_pre.cmp(b_pre),
+        }),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+fn read_metadata(version_dir: &Path) -> Result<ModelMetadata, ModelError> {
+    let path = version_dir.join(METADATA_FILE);
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| ModelError::Registry(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| ModelError::Registry(format!("Invalid {}: {}", path.display(), e)))
+}
+
+fn load_version(dir: &Path, version: &str, options: LoadOptions) -> Result<LoadedModel, ModelError> {
+    // Versions are directory names; refuse anything that could escape the registry
+    if version.is_empty() || version.contains(['/', '\\']) || version.starts_with('.') {
+        return Err(ModelError::UnknownVersion(version.to_string()));
+    }
+    let version_dir = dir.join(version);
+    if !version_dir.join(MODEL_FILE).is_file() {
+        return Err(ModelError::UnknownVersion(version.to_string()));
+    }
+
+    let metadata = read_metadata(&version_dir)?;
+    if metadata.version != version {
+        return Err(ModelError::Registry(format!(
+            "{} declares version '{}' but is stored as '{}'",
+            METADATA_FILE, metadata.version, version
+        )));
+    }
+
+    // A model that doesn't match its own schema never becomes active
+    let schema = FeatureSchema::new(metadata.feature_schema.clone(), options.out_of_range)?;
+    let model = RiskModel::from_file(version_dir.join(MODEL_FILE), schema, options.sessions)?;
+    Ok(LoadedModel {
+        metadata,
+        loaded_at: Utc::now(),
+        model: Arc::new(model),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_sort_numerically() {
+        let mut versions = vec!["1.10.0", "1.9.0", "1.10.0-rc.1", "2.0.0", "1.9.1"];
+        versions.sort_by(|a, b| compare_versions(a, b));
+        assert_eq!(versions, vec!["1.9.0", "1.9.1", "1.10.0-rc.1", "1.10.0", "2.0.0"]);
+    }
+
+    #[test]
+    fn test_reload_swaps_the_active_version() {
+        // A scratch registry with two copies of the bundled model
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("models").join("1.0.0");
+        let dir = std::env::temp_dir().join(format!("helios-registry-{}", std::process::id()));
+        for version in ["1.0.0", "1.1.0"] {
+            fs::create_dir_all(dir.join(version)).unwrap();
+            fs::copy(source.join(MODEL_FILE), dir.join(version).join(MODEL_FILE)).unwrap();
+            let metadata = fs::read_to_string(source.join(METADATA_FILE)).unwrap().replace("1.0.0", version);
+            fs::write(dir.join(version).join(METADATA_FILE), metadata).unwrap();
+        }
+        fs::write(dir.join(ACTIVE_FILE), "1.0.0\n").unwrap();
+
+        let options = LoadOptions {
+            out_of_range: OutOfRangePolicy::Clamp,
+            sessions: 1,
+        };
+        let registry = ModelRegistry::open(&dir, options).expect("Failed to open registry");
+        assert_eq!(registry.active().metadata.version, "1.0.0");
+        assert_eq!(registry.versions().unwrap().len(), 2);
+
+        let previous = registry.active();
+        registry.reload(Some("1.1.0")).expect("Reload failed");
+        assert_eq!(registry.active().metadata.version, "1.1.0");
+        assert_eq!(previous.metadata.version, "1.0.0"); // Still usable by in-flight requests
+        assert_eq!(fs::read_to_string(dir.join(ACTIVE_FILE)).unwrap().trim(), "1.1.0");
+
+        assert!(matches!(registry.reload(Some("9.9.9")), Err(ModelError::UnknownVersion(_))));
+        assert!(matches!(registry.reload(Some("../1.0.0")), Err(ModelError::UnknownVersion(_))));
+        assert_eq!(registry.active().metadata.version, "1.1.0");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
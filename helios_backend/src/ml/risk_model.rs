@@ -1,6 +1,19 @@
-use ort::{session::Session, value::Value};
+use ort::{
+    memory::Allocator,
+    session::Session,
+    tensor::TensorElementType,
+    value::{DynMapValueType, Value, ValueType},
+};
 use serde::{Deserialize, Serialize};
-use std::sync::RwLock;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use tokio::sync::Semaphore;
+
+use crate::units::{Speed, Temperature};
+
+use super::schema::{CheckedInput, FeatureSchema, MODEL_FEATURES};
+use super::ModelError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherData {
@@ -11,51 +24,290 @@ pub struct WeatherData {
     pub drought_index: f32, // 0-100 scale
 }
 
+impl WeatherData {
+    /// Build model features, converting to the Fahrenheit/mph units the model was trained on
+    pub fn from_conditions(
+        temperature: Temperature,
+        humidity: f32,
+        wind_speed: Speed,
+        precipitation: f32,
+        drought_index: f32,
+    ) -> Self {
+        WeatherData {
+            temperature: temperature.fahrenheit() as f32,
+            humidity,
+            wind_speed: wind_speed.miles_per_hour() as f32,
+            precipitation,
+            drought_index,
+        }
+    }
+}
+
+/// A risk score and any inputs the feature schema had to adjust to produce it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    pub probability: f32,
+    pub clamped: Vec<String>,
+}
+
+// sklearn classifiers exported by skl2onnx name their class probabilities one of these,
+// depending on whether the ZipMap post-processing step was kept
+const PROBABILITY_OUTPUTS: [&str; 2] = ["output_probability", "probabilities"];
+const RISK_CLASS: i64 = 1; // Classes are [no_risk, risk]
+
+/// How the model returns class probabilities
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProbabilityOutput {
+    Tensor,   // float [N, classes]
+    ClassMap, // sequence of {class: probability} maps, one per row
+}
+
+/// Identical sessions over one model. ONNX Runtime needs exclusive access to a session for
+/// each run, so concurrent requests take whichever session is free instead of queueing on one.
+/// Callers wait for a permit asynchronously; holding one guarantees a free session.
+struct SessionPool {
+    sessions: Vec<Mutex<Session>>,
+    permits: Arc<Semaphore>, // One per session
+    next: AtomicUsize,
+}
+
+impl SessionPool {
+    /// Only called on a blocking thread while holding a permit
+    fn acquire(&self) -> Result<MutexGuard<'_, Session>, ModelError> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let count = self.sessions.len();
+        for offset in 0..count {
+            match self.sessions[(start + offset) % count].try_lock() {
+                Ok(session) => return Ok(session),
+                Err(TryLockError::WouldBlock) => continue,
+                Err(TryLockError::Poisoned(_)) => return Err(ModelError::Poisoned),
+            }
+        }
+        // A permit means one is free or about to be released
+        self.sessions[start % count].lock().map_err(|_| ModelError::Poisoned)
+    }
+}
+
 pub struct RiskModel {
-    session: RwLock<Session>,
+    pool: SessionPool,
+    input_name: String,
+    max_batch: Option<usize>, // Set when the model fixes its batch dimension
+    output_name: String,
+    output_kind: ProbabilityOutput,
+    schema: FeatureSchema,
+}
+
+impl WeatherData {
+    /// Features in the order listed in `schema::MODEL_FEATURES`
+    pub fn features(&self) -> [f32; MODEL_FEATURES.len()] {
+        [
+            self.temperature,
+            self.humidity,
+            self.wind_speed,
+            self.precipitation,
+            self.drought_index,
+        ]
+    }
 }
 
 impl RiskModel {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        // Load the model file
-        let model_path = "model.onnx";        let session = Session::builder()?
-            .commit_from_file(model_path)?;
-        
-        Ok(RiskModel { session: RwLock::new(session) })
+    /// Load `sessions` copies of a model and check its declared inputs and outputs against `schema`
+    pub fn from_file(model_path: impl AsRef<Path>, schema: FeatureSchema, sessions: usize) -> Result<Self, ModelError> {
+        let model_path = model_path.as_ref();
+        let sessions = (0..sessions.max(1))
+            .map(|_| {
+                Session::builder()
+                    .and_then(|builder| builder.commit_from_file(model_path))
+                    .map(Mutex::new)
+                    .map_err(ModelError::Load)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let first = sessions[0].lock().map_err(|_| ModelError::Poisoned)?;
+        let (input_name, max_batch) = check_input(&first, &schema)?;
+        let (output_name, output_kind) = find_probability_output(&first)?;
+        drop(first);
+
+        Ok(RiskModel {
+            pool: SessionPool {
+                permits: Arc::new(Semaphore::new(sessions.len())),
+                sessions,
+                next: AtomicUsize::new(0),
+            },
+            input_name,
+            max_batch,
+            output_name,
+            output_kind,
+            schema,
+        })
     }
-      pub fn predict(&self, weather_data: &WeatherData) -> Result<f32, Box<dyn std::error::Error>> {
-        // Prepare input data as a flat vector
-        let input_data = vec![
-            weather_data.temperature,
-            weather_data.humidity,
-            weather_data.wind_speed,
-            weather_data.precipitation,
-            weather_data.drought_index,
-        ];
-        
-        // Convert to ONNX Value with correct shape (1, 5)
-        let input_tensor = Value::from_array(([1_usize, 5_usize], input_data))?;        // Run inference - use the ort::inputs! macro
-        let inputs = ort::inputs!["float_input" => input_tensor];
-        let mut session = self.session.write().unwrap();
-        let outputs = session.run(inputs)?;// Extract the probability of fire risk (class 1)
-        let output_tensor = outputs["output"].try_extract_tensor::<f32>()?;
-        let (_shape, data) = output_tensor;
-        
-        // The model outputs probabilities for both classes [no_risk, risk]
-        // We want the probability of risk (index 1)
-        let risk_probability = data[1];
-        
-        Ok(risk_probability)
+
+    pub async fn predict(self: &Arc<Self>, weather_data: &WeatherData) -> Result<Prediction, ModelError> {
+        self.predict_batch(std::slice::from_ref(weather_data))
+            .await?
+            .pop()
+            .ok_or_else(|| ModelError::MissingOutput(self.output_name.clone()))
     }
+
+    /// Score every row in as few session runs as the model allows, in input order.
+    /// One row outside the schema's ranges fails the whole batch under the reject policy.
+    /// Inference runs on the blocking thread pool so a busy model never stalls other requests.
+    pub async fn predict_batch(self: &Arc<Self>, weather_data: &[WeatherData]) -> Result<Vec<Prediction>, ModelError> {
+        let checked = weather_data
+            .iter()
+            .map(|w| self.schema.check(w.features()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let permit = self.pool.permits.clone().acquire_owned().await.map_err(|e| ModelError::Aborted(e.to_string()))?;
+        let model = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            model.run_batch(&checked)
+        })
+        .await
+        .map_err(|e| ModelError::Aborted(e.to_string()))?
+    }
+
+    fn run_batch(&self, checked: &[CheckedInput]) -> Result<Vec<Prediction>, ModelError> {
+        let mut session = self.pool.acquire()?;
+        let mut predictions = Vec::with_capacity(checked.len());
+        for chunk in checked.chunks(self.max_batch.unwrap_or(checked.len()).max(1)) {
+            let probabilities = self.run(&mut session, chunk)?;
+            predictions.extend(chunk.iter().zip(probabilities).map(|(row, probability)| Prediction {
+                probability,
+                clamped: row.clamped.clone(),
+            }));
+        }
+        Ok(predictions)
+    }
+
+    /// One session run over `[rows, features]`, returning the risk class probability per row
+    fn run(&self, session: &mut Session, rows: &[CheckedInput]) -> Result<Vec<f32>, ModelError> {
+        let input_data: Vec<f32> = rows.iter().flat_map(|row| row.values).collect();
+        let input_tensor = Value::from_array(([rows.len(), MODEL_FEATURES.len()], input_data))
+            .map_err(ModelError::Inference)?;
+        let inputs = ort::inputs![self.input_name.as_str() => input_tensor];
+
+        let outputs = session.run(inputs).map_err(ModelError::Inference)?;
+        let output = outputs
+            .get(&self.output_name)
+            .ok_or_else(|| ModelError::MissingOutput(self.output_name.clone()))?;
+
+        let probabilities: Vec<f32> = match self.output_kind {
+            ProbabilityOutput::Tensor => {
+                let (shape, data) = output.try_extract_tensor::<f32>().map_err(ModelError::Inference)?;
+                let classes = shape.get(1).copied().unwrap_or(0).max(0) as usize;
+                data.chunks(classes.max(1))
+                    .filter_map(|row| row.get(RISK_CLASS as usize).copied())
+                    .collect()
+            }
+            ProbabilityOutput::ClassMap => output
+                .try_extract_sequence::<DynMapValueType>(&Allocator::default())
+                .map_err(ModelError::Inference)?
+                .iter()
+                .map(|row| {
+                    let classes = row.try_extract_map::<i64, f32>().map_err(ModelError::Inference)?;
+                    classes
+                        .get(&RISK_CLASS)
+                        .copied()
+                        .ok_or_else(|| ModelError::MissingOutput(self.output_name.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        // A row without a risk class probability would silently shift every later score
+        if probabilities.len() != rows.len() {
+            return Err(ModelError::MissingOutput(self.output_name.clone()));
+        }
+        Ok(probabilities)
+    }
+}
+
+/// The model must take a single float tensor with one column per schema feature. Returns the
+/// input name and the batch size, if the model fixes one.
+fn check_input(session: &Session, schema: &FeatureSchema) -> Result<(String, Option<usize>), ModelError> {
+    let [input] = session.inputs.as_slice() else {
+        return Err(ModelError::Schema(format!(
+            "expected a single input, the model declares {}",
+            session.inputs.len()
+        )));
+    };
+
+    let columns = schema.feature_count() as i64;
+    let matches = match &input.input_type {
+        ValueType::Tensor { ty: TensorElementType::Float32, shape, .. } => {
+            shape.len() == 2 && (shape[1] == columns || shape[1] < 0)
+        }
+        _ => false,
+    };
+    if !matches {
+        return Err(ModelError::Schema(format!(
+            "input '{}' is {}, expected a float32 tensor of shape [N, {}]",
+            input.name, input.input_type, columns
+        )));
+    }
+    let max_batch = input
+        .input_type
+        .tensor_shape()
+        .and_then(|shape| shape.first())
+        .filter(|rows| **rows > 0)
+        .map(|rows| *rows as usize);
+    Ok((input.name.clone(), max_batch))
+}
+
+fn find_probability_output(session: &Session) -> Result<(String, ProbabilityOutput), ModelError> {
+    for output in &session.outputs {
+        if !PROBABILITY_OUTPUTS.contains(&output.name.as_str()) {
+            continue;
+        }
+        let kind = match &output.output_type {
+            ValueType::Tensor { ty: TensorElementType::Float32, shape, .. }
+                if shape.len() == 2 && (shape[1] < 0 || shape[1] > RISK_CLASS) =>
+            {
+                Some(ProbabilityOutput::Tensor)
+            }
+            ValueType::Sequence(inner) => match inner.as_ref() {
+                ValueType::Map { key: TensorElementType::Int64, value: TensorElementType::Float32 } => {
+                    Some(ProbabilityOutput::ClassMap)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        return match kind {
+            Some(kind) => Ok((output.name.clone(), kind)),
+            None => Err(ModelError::Schema(format!(
+                "output '{}' is {}, expected class probabilities",
+                output.name, output.output_type
+            ))),
+        };
+    }
+
+    let declared: Vec<&str> = session.outputs.iter().map(|o| o.name.as_str()).collect();
+    Err(ModelError::Schema(format!(
+        "no class probability output ({}); the model declares [{}]",
+        PROBABILITY_OUTPUTS.join(" or "),
+        declared.join(", ")
+    )))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ml::registry::LoadOptions;
+    use crate::ml::{ModelRegistry, OutOfRangePolicy};
     
-    #[test]
-    fn test_risk_model_prediction() {
-        let model = RiskModel::new().expect("Failed to load model");
+    #[tokio::test]
+    async fn test_risk_model_prediction() {
+        // Loading through the registry checks the model against its feature schema
+        let options = LoadOptions {
+            out_of_range: OutOfRangePolicy::Reject,
+            sessions: 2,
+        };
+        let registry = ModelRegistry::open(concat!(env!("CARGO_MANIFEST_DIR"), "/models"), options)
+            .expect("Failed to load model");
+        let active = registry.active();
+        let model = active.model();
         
         // Test with high risk conditions
         let high_risk_weather = WeatherData {
@@ -66,7 +318,7 @@ mod tests {
             drought_index: 90.0,
         };
         
-        let risk = model.predict(&high_risk_weather).expect("Prediction failed");
+        let risk = model.predict(&high_risk_weather).await.expect("Prediction failed").probability;
         assert!(risk > 0.8, "High risk conditions should return high probability");
         
         // Test with low risk conditions
@@ -78,7 +330,26 @@ mod tests {
             drought_index: 10.0,
         };
         
-        let risk = model.predict(&low_risk_weather).expect("Prediction failed");
+        let risk = model.predict(&low_risk_weather).await.expect("Prediction failed").probability;
         assert!(risk < 0.2, "Low risk conditions should return low probability");
+
+        // One batched run scores each row as if it were alone
+        let batch = model
+            .predict_batch(&[high_risk_weather.clone(), low_risk_weather.clone()])
+            .await
+            .expect("Batch prediction failed");
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0], model.predict(&high_risk_weather).await.unwrap());
+        assert_eq!(batch[1], model.predict(&low_risk_weather).await.unwrap());
+
+        // More concurrent requests than sessions wait their turn instead of blocking a worker
+        let mut requests = tokio::task::JoinSet::new();
+        for _ in 0..8 {
+            let (model, weather) = (model.clone(), high_risk_weather.clone());
+            requests.spawn(async move { model.predict(&weather).await });
+        }
+        while let Some(prediction) = requests.join_next().await {
+            assert_eq!(prediction.unwrap().unwrap(), batch[0]);
+        }
     }
 }
@@ -0,0 +1,195 @@
+// The feature schema a model version ships with in its `metadata.json`. It pins the order,
+// units and training ranges of the model's inputs so a mismatched or retrained model fails at
+// load time instead of scoring garbage, and so inputs the model never saw are caught.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::ModelError;
+
+/// The features `WeatherData::features` produces, in order, with the unit each is given in
+pub const MODEL_FEATURES: [(&str, &str); 5] = [
+    ("temperature", "°F"),
+    ("humidity", "%"),
+    ("wind_speed", "mph"),
+    ("precipitation", "in"),
+    ("drought_index", "0-100"),
+];
+
+/// One model input, in the order the model expects them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureSpec {
+    pub name: String,
+    pub unit: Option<String>,
+    pub min: Option<f32>, // Range seen in training
+    pub max: Option<f32>,
+}
+
+/// What to do with an input outside the range the model was trained on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutOfRangePolicy {
+    #[default]
+    Clamp, // Score with the nearest value the model has seen
+    Reject,
+}
+
+impl FromStr for OutOfRangePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "clamp" => Ok(OutOfRangePolicy::Clamp),
+            "reject" => Ok(OutOfRangePolicy::Reject),
+            other => Err(format!("Unknown out-of-range policy '{}'; expected clamp or reject", other)),
+        }
+    }
+}
+
+impl fmt::Display for OutOfRangePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutOfRangePolicy::Clamp => write!(f, "clamp"),
+            OutOfRangePolicy::Reject => write!(f, "reject"),
+        }
+    }
+}
+
+/// A validated feature schema plus the policy for applying it
+#[derive(Debug, Clone)]
+pub struct FeatureSchema {
+    features: Vec<FeatureSpec>,
+    policy: OutOfRangePolicy,
+}
+
+/// Model inputs after the schema has been applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedInput {
+    pub values: [f32; MODEL_FEATURES.len()],
+    pub clamped: Vec<String>, // Features moved into their training range
+}
+
+impl FeatureSchema {
+    /// Check the declared features and units against the ones this build feeds the model
+    pub fn new(features: Vec<FeatureSpec>, policy: OutOfRangePolicy) -> Result<Self, ModelError> {
+        let names: Vec<&str> = features.iter().map(|f| f.name.as_str()).collect();
+        let expected: Vec<&str> = MODEL_FEATURES.iter().map(|(name, _)| *name).collect();
+        if names != expected {
+            return Err(ModelError::Schema(format!(
+                "feature_schema lists [{}] but the server provides [{}]",
+                names.join(", "),
+                expected.join(", ")
+            )));
+        }
+        for (feature, (_, unit)) in features.iter().zip(MODEL_FEATURES) {
+            if feature.unit.as_deref() != Some(unit) {
+                return Err(ModelError::Schema(format!(
+                    "feature '{}' is declared in {} but the server provides {}",
+                    feature.name,
+                    feature.unit.as_deref().unwrap_or("no unit"),
+                    unit
+                )));
+            }
+            if let (Some(min), Some(max)) = (feature.min, feature.max) {
+                if min > max {
+                    return Err(ModelError::Schema(format!(
+                        "feature '{}' has min {} above max {}",
+                        feature.name, min, max
+                    )));
+                }
+            }
+        }
+        Ok(FeatureSchema { features, policy })
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Apply the schema to one row of features. Non-finite values are always rejected.
+    pub fn check(&self, values: [f32; MODEL_FEATURES.len()]) -> Result<CheckedInput, ModelError> {
+        let mut checked = values;
+        let mut clamped = Vec::new();
+
+        for (value, feature) in checked.iter_mut().zip(&self.features) {
+            if !value.is_finite() {
+                return Err(self.out_of_range(feature, *value));
+            }
+            let min = feature.min.unwrap_or(f32::NEG_INFINITY);
+            let max = feature.max.unwrap_or(f32::INFINITY);
+            if *value >= min && *value <= max {
+                continue;
+            }
+            match self.policy {
+                OutOfRangePolicy::Reject => return Err(self.out_of_range(feature, *value)),
+                OutOfRangePolicy::Clamp => {
+                    *value = value.clamp(min, max);
+                    clamped.push(feature.name.clone());
+                }
+            }
+        }
+
+        Ok(CheckedInput { values: checked, clamped })
+    }
+
+    fn out_of_range(&self, feature: &FeatureSpec, value: f32) -> ModelError {
+        let unit = feature.unit.as_deref().map(|u| format!(" {}", u)).unwrap_or_default();
+        let bound = |b: Option<f32>| b.map_or("unbounded".to_string(), |b| b.to_string());
+        ModelError::OutOfRange(format!(
+            "{} = {}{} is outside the model's valid range {} to {}",
+            feature.name,
+            value,
+            unit,
+            bound(feature.min),
+            bound(feature.max)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(policy: OutOfRangePolicy) -> FeatureSchema {
+        let features = MODEL_FEATURES
+            .iter()
+            .map(|(name, unit)| FeatureSpec {
+                name: name.to_string(),
+                unit: Some(unit.to_string()),
+                min: Some(0.0),
+                max: Some(100.0),
+            })
+            .collect();
+        FeatureSchema::new(features, policy).unwrap()
+    }
+
+    #[test]
+    fn test_out_of_range_inputs_are_clamped_or_rejected() {
+        let row = [120.0, 50.0, -3.0, 1.0, 40.0];
+
+        let checked = schema(OutOfRangePolicy::Clamp).check(row).unwrap();
+        assert_eq!(checked.values, [100.0, 50.0, 0.0, 1.0, 40.0]);
+        assert_eq!(checked.clamped, vec!["temperature", "wind_speed"]);
+
+        let err = schema(OutOfRangePolicy::Reject).check(row).unwrap_err();
+        assert!(err.to_string().contains("temperature = 120"), "{}", err);
+
+        // NaN has no nearest valid value
+        assert!(schema(OutOfRangePolicy::Clamp).check([f32::NAN, 50.0, 5.0, 1.0, 40.0]).is_err());
+
+        let mut reordered: Vec<FeatureSpec> = schema(OutOfRangePolicy::Clamp).features;
+        reordered.swap(0, 1);
+        assert!(matches!(FeatureSchema::new(reordered, OutOfRangePolicy::Clamp), Err(ModelError::Schema(_))));
+    }
+
+    #[test]
+    fn test_schema_in_other_units_is_rejected() {
+        let mut celsius: Vec<FeatureSpec> = schema(OutOfRangePolicy::Clamp).features;
+        celsius[0].unit = Some("°C".to_string());
+
+        let err = FeatureSchema::new(celsius, OutOfRangePolicy::Clamp).unwrap_err();
+        assert!(matches!(err, ModelError::Schema(_)));
+        assert!(err.to_string().contains("°C"), "{}", err);
+    }
+}
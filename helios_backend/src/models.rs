@@ -1,5 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+// Parameter names used for rows in sensor_readings. Individual pollutants are
+// stored under the AirNow parameter name ("O3", "PM2.5", ...) with their AQI.
+pub const PARAM_AQI: &str = "AQI";
+pub const PARAM_TEMPERATURE: &str = "temperature";
+pub const PARAM_HUMIDITY: &str = "humidity";
+pub const PARAM_WIND_SPEED: &str = "wind_speed";
+pub const PARAM_WIND_DIRECTION: &str = "wind_direction";
+pub const PARAM_PRECIPITATION: &str = "precipitation"; // mm in the hour before observed_at
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sensor {
     pub id: i32,
@@ -17,14 +26,130 @@ pub struct SensorLocation {
     pub data_source: String,
     pub latitude: f64,
     pub longitude: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub latest_readings: Vec<SensorReading>, // Most recent reading per parameter, when loaded
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct NewSensor {
     pub name: String,
     pub data_source: String,
     pub latitude: f64,
     pub longitude: f64,
+    pub external_id: Option<String>, // The data source's own id, e.g. AirNow AQSID or PurpleAir sensor index
+    pub elevation: Option<f64>,      // Meters above sea level
+    pub agency: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<String>, // What the station measures, e.g. "PM2.5", "O3"
+    #[serde(default = "default_active")]
+    pub active: bool, // Inactive stations are kept but not polled
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl NewSensor {
+    /// Check the fields against the `sensors` columns before they reach the database
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.chars().count() > 255 {
+            return Err("name must be between 1 and 255 characters".to_string());
+        }
+        if self.data_source.trim().is_empty() || self.data_source.chars().count() > 100 {
+            return Err("data_source must be between 1 and 100 characters".to_string());
+        }
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!("latitude must be between -90 and 90, got {}", self.latitude));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!("longitude must be between -180 and 180, got {}", self.longitude));
+        }
+        if self.external_id.as_ref().is_some_and(|id| id.trim().is_empty() || id.chars().count() > 100) {
+            return Err("external_id must be between 1 and 100 characters".to_string());
+        }
+        if self.elevation.is_some_and(|elevation| !elevation.is_finite()) {
+            return Err("elevation must be a number of meters".to_string());
+        }
+        if self.agency.as_ref().is_some_and(|agency| agency.chars().count() > 255) {
+            return Err("agency must be at most 255 characters".to_string());
+        }
+        if self.parameters.iter().any(|parameter| parameter.trim().is_empty()) {
+            return Err("parameters must not contain empty names".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A partial update to a sensor; missing fields keep their current values and the optional
+/// ones are cleared with null
+#[derive(Debug, Default, Deserialize)]
+pub struct SensorUpdate {
+    pub name: Option<String>,
+    pub data_source: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub external_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub elevation: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub agency: Option<Option<String>>,
+    pub parameters: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+impl SensorUpdate {
+    /// The sensor as it will be stored
+    pub fn apply(self, current: &SensorDetail) -> NewSensor {
+        NewSensor {
+            name: self.name.unwrap_or_else(|| current.name.clone()),
+            data_source: self.data_source.unwrap_or_else(|| current.data_source.clone()),
+            latitude: self.latitude.unwrap_or(current.latitude),
+            longitude: self.longitude.unwrap_or(current.longitude),
+            external_id: self.external_id.unwrap_or_else(|| current.external_id.clone()),
+            elevation: self.elevation.unwrap_or(current.elevation),
+            agency: self.agency.unwrap_or_else(|| current.agency.clone()),
+            parameters: self.parameters.unwrap_or_else(|| current.parameters.clone()),
+            active: self.active.unwrap_or(current.active),
+        }
+    }
+}
+
+/// A present field, null or not; `#[serde(default)]` leaves a missing one as `None`
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// What `Database::upsert_sensor` did with a sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpsertOutcome {
+    Inserted,
+    Updated,
+    Unchanged,
+    Decommissioned, // Matched a decommissioned sensor, which is left alone
+}
+
+/// A sensor with its bookkeeping columns, decommissioned or not
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorDetail {
+    pub id: i32,
+    pub name: String,
+    pub data_source: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub external_id: Option<String>,
+    pub elevation: Option<f64>, // Meters above sea level
+    pub agency: Option<String>,
+    pub parameters: Vec<String>,
+    pub active: bool,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub decommissioned_at: Option<chrono::DateTime<chrono::Utc>>, // No longer polled or listed
 }
 
 impl From<Sensor> for SensorLocation {
@@ -53,6 +178,169 @@ impl From<Sensor> for SensorLocation {
             data_source: sensor.data_source,
             latitude,
             longitude,
+            latest_readings: Vec::new(),
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub id: i64,
+    pub sensor_id: i32,
+    pub parameter: String, // e.g. "PM2.5", "O3", "temperature"
+    pub value: Option<f64>,
+    pub unit: Option<String>,
+    pub aqi: Option<i32>,
+    pub observed_at: chrono::DateTime<chrono::Utc>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSensorReading {
+    pub sensor_id: i32,
+    pub parameter: String,
+    pub value: Option<f64>,
+    pub unit: Option<String>,
+    pub aqi: Option<i32>,
+    pub observed_at: chrono::DateTime<chrono::Utc>,
+    pub source: String,
+}
+
+/// Precipitation recorded for one sensor in one clock hour
+#[derive(Debug, Clone)]
+pub struct HourlyPrecipitation {
+    pub sensor_id: i32,
+    pub hour: chrono::DateTime<chrono::Utc>,
+    pub millimeters: f64,
+}
+
+/// One UTC day of stored weather for a sensor
+#[derive(Debug, Clone)]
+pub struct DailyWeather {
+    pub date: chrono::NaiveDate,
+    pub min_temperature_c: Option<f64>,
+    pub max_temperature_c: Option<f64>,
+    pub min_humidity: Option<f64>,
+    pub max_humidity: Option<f64>,
+    pub rainfall_mm: Option<f64>, // None when no precipitation was recorded at all
+    pub wet_hours: i32,           // Clock hours with measurable rain
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KbdiDay {
+    pub sensor_id: i32,
+    pub date: chrono::NaiveDate,
+    pub kbdi: f64,
+    pub spell_rainfall: f64,
+    pub max_temperature: Option<f64>, // °C
+    pub rainfall: Option<f64>,        // mm
+}
+
+/// Stored weather at local noon for one day, the Fire Weather Index inputs
+#[derive(Debug, Clone)]
+pub struct NoonObservation {
+    pub date: chrono::NaiveDate,
+    pub temperature_c: Option<f64>,
+    pub humidity: Option<f64>,
+    pub wind_speed_ms: Option<f64>,
+    pub rainfall_mm: Option<f64>, // 24 hours ending at noon
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FwiDay {
+    pub sensor_id: i32,
+    pub date: chrono::NaiveDate,
+    pub ffmc: f64,
+    pub dmc: f64,
+    pub dc: f64,
+    pub isi: f64,
+    pub bui: f64,
+    pub fwi: f64,
+    pub temperature: Option<f64>, // °C
+    pub humidity: Option<f64>,    // %
+    pub wind_speed: Option<f64>,  // km/h
+    pub rainfall: Option<f64>,    // mm
+}
+
+/// Tracked NFDRS heavy dead fuel moistures for one day
+#[derive(Debug, Clone, Serialize)]
+pub struct NfdrsDay {
+    pub sensor_id: i32,
+    pub date: chrono::NaiveDate,
+    pub hundred_hour: f64,           // %
+    pub thousand_hour: f64,          // %
+    pub thousand_hour_boundary: f64, // The day's 1000-hour boundary condition, averaged over a week
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestionRun {
+    pub id: i64,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+    pub sensors_total: i32,
+    pub sensors_succeeded: i32,
+    pub sensors_failed: i32,
+    pub readings_inserted: i64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct NewIngestionRun {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+    pub sensors_total: i32,
+    pub sensors_succeeded: i32,
+    pub sensors_failed: i32,
+    pub readings_inserted: i64,
+    pub errors: Vec<String>,
+}
+
+/// One scored cell of a risk grid, as stored for the risk map tiles
+#[derive(Debug, Clone)]
+pub struct NewRiskGridCell {
+    pub resolution: f64, // Cell size in degrees
+    pub south: f64,      // South-west corner
+    pub west: f64,
+    pub probability: f32,
+    pub level: String,
+    pub model_version: String,
+    pub computed_at: chrono::DateTime<chrono::Utc>, // Start of the pass that scored it
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensor_update_null_clears_optional_fields() {
+        let current = SensorDetail {
+            id: 4,
+            name: "Bend-NE 27th Street".to_string(),
+            data_source: "AirNow".to_string(),
+            latitude: 44.06,
+            longitude: -121.28,
+            external_id: Some("410170004".to_string()),
+            elevation: Some(1105.0),
+            agency: Some("Oregon DEQ".to_string()),
+            parameters: vec!["PM2.5".to_string()],
+            active: true,
+            created_at: None,
+            updated_at: None,
+            decommissioned_at: None,
+        };
+
+        let missing: SensorUpdate = serde_json::from_str(r#"{"name": "Bend NE"}"#).unwrap();
+        let kept = missing.apply(&current);
+        assert_eq!(kept.name, "Bend NE");
+        assert_eq!(kept.external_id.as_deref(), Some("410170004"));
+        assert_eq!(kept.elevation, Some(1105.0));
+        assert_eq!(kept.agency.as_deref(), Some("Oregon DEQ"));
+
+        let null: SensorUpdate =
+            serde_json::from_str(r#"{"external_id": null, "elevation": null, "agency": null}"#).unwrap();
+        let cleared = null.apply(&current);
+        assert_eq!(cleared.external_id, None);
+        assert_eq!(cleared.elevation, None);
+        assert_eq!(cleared.agency, None);
+    }
+}
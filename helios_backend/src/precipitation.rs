@@ -0,0 +1,150 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::database::Database;
+use crate::models::PARAM_PRECIPITATION;
+use crate::models::{HourlyPrecipitation, SensorLocation};
+use crate::units::{Precipitation, UnitSystem};
+
+// The risk model was trained on rain in the previous 7 days
+pub const WINDOW_DAYS: i64 = 7;
+
+/// Hourly precipitation recorded at one sensor
+#[derive(Debug, Clone)]
+pub struct PrecipitationHistory {
+    pub sensor_id: i32,
+    hourly: Vec<HourlyPrecipitation>,
+}
+
+/// What went into a precipitation total, reported alongside risk scores
+#[derive(Debug, Serialize)]
+pub struct PrecipitationCoverage {
+    pub sensor_id: Option<i32>,
+    pub total: f64, // In the requested units
+    pub window_days: i64,
+    pub hours_covered: usize,
+    pub hours_expected: i64,
+    pub coverage: f64, // Fraction of the window with a recorded observation
+}
+
+impl PrecipitationHistory {
+    pub fn new(sensor_id: i32, hourly: Vec<HourlyPrecipitation>) -> Self {
+        PrecipitationHistory { sensor_id, hourly }
+    }
+
+    fn hours_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> impl Iterator<Item = &HourlyPrecipitation> {
+        self.hourly.iter().filter(move |h| h.hour >= start && h.hour <= end)
+    }
+
+    /// Recorded precipitation in `[start, end]`. Hours without an observation count as dry.
+    pub fn total_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Precipitation {
+        Precipitation::from_millimeters(self.hours_between(start, end).map(|h| h.millimeters).sum())
+    }
+
+    pub fn hours_covered_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> usize {
+        self.hours_between(start, end).count()
+    }
+}
+
+/// Trailing precipitation for the nearest sensor that has any recorded.
+/// Sensors are expected in nearest-first order, as returned by `get_sensors_near_point`.
+pub async fn trailing_history(
+    db: &Database,
+    sensors: &[SensorLocation],
+    now: DateTime<Utc>,
+) -> Result<Option<PrecipitationHistory>, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    if sensor_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let hourly = db
+        .get_hourly_precipitation(&sensor_ids, PARAM_PRECIPITATION, now - Duration::days(WINDOW_DAYS))
+        .await?;
+
+    Ok(sensor_ids.into_iter().find_map(|sensor_id| {
+        let sensor_hours: Vec<HourlyPrecipitation> =
+            hourly.iter().filter(|h| h.sensor_id == sensor_id).cloned().collect();
+        (!sensor_hours.is_empty()).then(|| PrecipitationHistory::new(sensor_id, sensor_hours))
+    }))
+}
+
+/// Trailing 7-day total for each sensor. Sensors with nothing recorded are absent from the map.
+pub async fn trailing_totals(
+    db: &Database,
+    sensors: &[SensorLocation],
+    now: DateTime<Utc>,
+) -> Result<HashMap<i32, Precipitation>, sqlx::Error> {
+    let sensor_ids: Vec<i32> = sensors.iter().map(|s| s.id).collect();
+    if sensor_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let start = now - Duration::days(WINDOW_DAYS);
+    let hourly = db.get_hourly_precipitation(&sensor_ids, PARAM_PRECIPITATION, start).await?;
+
+    let mut totals: HashMap<i32, f64> = HashMap::new();
+    for hour in hourly.iter().filter(|h| h.hour <= now) {
+        *totals.entry(hour.sensor_id).or_default() += hour.millimeters;
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(sensor_id, millimeters)| (sensor_id, Precipitation::from_millimeters(millimeters)))
+        .collect())
+}
+
+/// Total and coverage for the window ending at `end`. With no history the total is zero
+/// and the coverage says so.
+pub fn coverage(history: Option<&PrecipitationHistory>, end: DateTime<Utc>, units: UnitSystem) -> PrecipitationCoverage {
+    let start = end - Duration::days(WINDOW_DAYS);
+    let hours_expected = WINDOW_DAYS * 24;
+    let (sensor_id, total, hours_covered) = match history {
+        Some(history) => (
+            Some(history.sensor_id),
+            history.total_between(start, end),
+            history.hours_covered_between(start, end),
+        ),
+        None => (None, Precipitation::from_millimeters(0.0), 0),
+    };
+
+    PrecipitationCoverage {
+        sensor_id,
+        total: total.in_units(units),
+        window_days: WINDOW_DAYS,
+        hours_covered,
+        hours_expected,
+        coverage: hours_covered as f64 / hours_expected as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_trailing_total_only_counts_hours_inside_the_window() {
+        let now = Utc.with_ymd_and_hms(2025, 8, 14, 12, 0, 0).unwrap();
+        let hour = |hours_ago: i64, millimeters: f64| HourlyPrecipitation {
+            sensor_id: 3,
+            hour: now - Duration::hours(hours_ago),
+            millimeters,
+        };
+        let history = PrecipitationHistory::new(3, vec![
+            hour(200, 9.0), // Outside the 7 day window
+            hour(100, 2.5),
+            hour(30, 0.0),
+            hour(2, 1.3),
+        ]);
+
+        let summary = coverage(Some(&history), now, UnitSystem::Metric);
+        assert!((summary.total - 3.8).abs() < 1e-9, "expected 3.8 mm, got {}", summary.total);
+        assert_eq!(summary.hours_covered, 3);
+        assert_eq!(summary.hours_expected, 168);
+
+        let empty = coverage(None, now, UnitSystem::Metric);
+        assert_eq!(empty.total, 0.0);
+        assert_eq!(empty.coverage, 0.0);
+    }
+}
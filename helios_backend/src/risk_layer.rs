@@ -0,0 +1,181 @@
+// The stored risk layer behind the `risk` map tiles. A background pass scores every whole-degree
+// block that has a sensor with fresh observations, on a global lattice so that passes overwrite
+// the same cells, and then drops cells the pass did not rescore.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::{IndicesConfig, RiskLayerConfig};
+use crate::database::Database;
+use crate::grid::{self, GridSpec, Sample};
+use crate::indices::{self, KbdiState};
+use crate::ingestion;
+use crate::ml::{ModelRegistry, WeatherData};
+use crate::models::{NewRiskGridCell, SensorLocation};
+use crate::precipitation;
+
+/// Start the risk layer job on the tokio runtime. The first pass runs immediately.
+pub fn spawn(
+    db: Arc<Database>,
+    models: Arc<ModelRegistry>,
+    indices: IndicesConfig,
+    config: RiskLayerConfig,
+    max_reading_age: chrono::Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let now = Utc::now();
+            match update_layer(&db, &models, &indices, config.resolution, now - max_reading_age, now).await {
+                Ok(0) => {}
+                Ok(cells) => println!("Risk layer: {} cells scored", cells),
+                Err(e) => eprintln!("Failed to update the risk layer: {}", e),
+            }
+        }
+    })
+}
+
+/// Score and store every lattice block with fresh observations, then delete the cells scored
+/// before this pass. Nothing is deleted when a block fails, so its last scores stay visible.
+pub async fn update_layer(
+    db: &Database,
+    models: &ModelRegistry,
+    indices: &IndicesConfig,
+    resolution: f64,
+    cutoff: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let sensors = db.get_active_sensors().await?;
+    let samples = observed_samples(db, indices, &sensors, cutoff, now).await?;
+
+    let mut stored = 0;
+    let mut complete = true;
+    for block in lattice_blocks(&samples, resolution) {
+        let (min_lat, min_lon, max_lat, max_lon) = block.sample_bounds();
+        let nearby: Vec<Sample> = samples
+            .iter()
+            .filter(|s| (min_lat..=max_lat).contains(&s.latitude) && (min_lon..=max_lon).contains(&s.longitude))
+            .cloned()
+            .collect();
+        let cells: Vec<WeatherData> = block
+            .cells()
+            .filter_map(|(_, _, lat, lon)| grid::interpolate(&nearby, lat, lon))
+            .collect();
+
+        let model = models.active();
+        let predictions = match model.model().predict_batch(&cells).await {
+            Ok(predictions) => predictions,
+            Err(e) => {
+                eprintln!("Failed to score risk block at {}, {}: {}", block.min_lat, block.min_lon, e);
+                complete = false;
+                continue;
+            }
+        };
+
+        let scored: Vec<NewRiskGridCell> = block
+            .cells()
+            .zip(&predictions)
+            .map(|((row, col, _, _), prediction)| {
+                let (south, west, _, _) = block.cell_bounds(row, col);
+                NewRiskGridCell {
+                    resolution: block.resolution,
+                    south,
+                    west,
+                    probability: prediction.probability,
+                    level: crate::get_risk_level(prediction.probability).to_string(),
+                    model_version: model.metadata.version.clone(),
+                    computed_at: now,
+                }
+            })
+            .collect();
+        stored += db.upsert_risk_grid_cells(&scored).await?;
+    }
+
+    if complete {
+        db.delete_risk_grid_cells_before(now).await?;
+    }
+    Ok(stored)
+}
+
+/// Model features at each sensor with a stored reading newer than `cutoff`
+pub async fn observed_samples(
+    db: &Database,
+    indices: &IndicesConfig,
+    sensors: &[SensorLocation],
+    cutoff: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<Vec<Sample>, sqlx::Error> {
+    let observed: Vec<(&SensorLocation, crate::clients::WeatherData)> = sensors
+        .iter()
+        .filter_map(|sensor| ingestion::stored_weather(std::slice::from_ref(sensor), cutoff).map(|w| (sensor, w)))
+        .collect();
+    if observed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rainfall = precipitation::trailing_totals(db, sensors, now).await?;
+    let kbdi = indices::daily::current_kbdi_by_sensor(db, sensors, indices, now.date_naive()).await?;
+    Ok(observed
+        .into_iter()
+        .map(|(sensor, weather)| Sample {
+            latitude: sensor.latitude,
+            longitude: sensor.longitude,
+            features: WeatherData::from_conditions(
+                weather.temperature,
+                weather.humidity as f32,
+                weather.wind_speed,
+                rainfall.get(&sensor.id).map_or(0.0, |total| total.inches() as f32),
+                kbdi.get(&sensor.id)
+                    .map_or_else(|| KbdiState::new(indices.kbdi_initial), |k| k.state)
+                    .model_input(),
+            ),
+        })
+        .collect())
+}
+
+/// The lattice blocks holding at least one sample, south-west first
+fn lattice_blocks(samples: &[Sample], resolution: f64) -> Vec<GridSpec> {
+    let mut blocks = BTreeMap::new();
+    for sample in samples {
+        if let Ok(block) = GridSpec::lattice_block(sample.latitude, sample.longitude, resolution) {
+            blocks.entry((block.min_lat as i32, block.min_lon as i32)).or_insert(block);
+        }
+    }
+    blocks.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latitude: f64, longitude: f64) -> Sample {
+        Sample {
+            latitude,
+            longitude,
+            features: WeatherData {
+                temperature: 25.0,
+                humidity: 30.0,
+                wind_speed: 3.0,
+                precipitation: 0.0,
+                drought_index: 100.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_each_block_with_a_sample_is_scored_once() {
+        let samples = [sample(44.13, -121.77), sample(44.9, -121.2), sample(42.2, -121.77), sample(-33.9, 151.2)];
+        let blocks = lattice_blocks(&samples, 0.05);
+
+        let origins: Vec<(f64, f64)> = blocks.iter().map(|b| (b.min_lat, b.min_lon)).collect();
+        assert_eq!(origins, vec![(-34.0, 151.0), (42.0, -122.0), (44.0, -122.0)]);
+        assert!(blocks.iter().all(|b| b.cell_count() == 400));
+    }
+}
@@ -0,0 +1,192 @@
+// Mapbox Vector Tiles for the map, rendered by PostGIS with ST_AsMVT. Tiles are addressed in
+// the usual web mercator z/x/y scheme and cached in memory for a short time, with an ETag so
+// clients can revalidate without downloading an unchanged tile again.
+
+use chrono::{Duration as ChronoDuration, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::database::Database;
+
+pub const CONTENT_TYPE: &str = "application/vnd.mapbox-vector-tile";
+pub const MAX_ZOOM: u32 = 22;
+
+// Anything older is not "latest" enough to put on the map
+const READINGS_MAX_AGE_HOURS: i64 = 24;
+const RISK_MAX_AGE_HOURS: i64 = 6;
+
+type CachedTiles = HashMap<(TileLayer, TileCoord), (Instant, Arc<Tile>)>;
+
+/// The layers tiles are served for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileLayer {
+    Sensors,  // Every station
+    Readings, // Stations with their latest observations
+    Risk,     // Recently scored risk grid cells
+}
+
+impl FromStr for TileLayer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sensors" => Ok(TileLayer::Sensors),
+            "readings" => Ok(TileLayer::Readings),
+            "risk" => Ok(TileLayer::Risk),
+            other => Err(format!("Unknown tile layer '{}'; expected sensors, readings or risk", other)),
+        }
+    }
+}
+
+impl fmt::Display for TileLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileLayer::Sensors => write!(f, "sensors"),
+            TileLayer::Readings => write!(f, "readings"),
+            TileLayer::Risk => write!(f, "risk"),
+        }
+    }
+}
+
+/// A web mercator tile address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileCoord {
+    /// `y` may carry the `.mvt` extension, as in `/tiles/sensors/8/41/92.mvt`
+    pub fn parse(z: u32, x: u32, y: &str) -> Result<Self, String> {
+        let y: u32 = y
+            .strip_suffix(".mvt")
+            .unwrap_or(y)
+            .parse()
+            .map_err(|_| format!("Invalid tile row '{}'", y))?;
+        if z > MAX_ZOOM {
+            return Err(format!("Zoom must be at most {}, got {}", MAX_ZOOM, z));
+        }
+        let tiles_across = 1u32 << z;
+        if x >= tiles_across || y >= tiles_across {
+            return Err(format!("Tile {}/{}/{} is outside the {}x{} grid at zoom {}", z, x, y, tiles_across, tiles_across, z));
+        }
+        Ok(TileCoord { z, x, y })
+    }
+}
+
+/// An encoded tile and the ETag it is served with
+#[derive(Debug)]
+pub struct Tile {
+    pub data: Vec<u8>,
+    pub etag: String,
+}
+
+impl Tile {
+    fn new(data: Vec<u8>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        Tile {
+            etag: format!("\"{:016x}\"", hasher.finish()),
+            data,
+        }
+    }
+
+    /// Whether an `If-None-Match` header already names this tile
+    pub fn matches(&self, if_none_match: &str) -> bool {
+        if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == self.etag)
+    }
+}
+
+/// Recently rendered tiles. Rendering hits PostGIS, and a map view asks for the same handful of
+/// tiles from every client.
+pub struct TileCache {
+    ttl: Duration,
+    max_entries: usize,
+    tiles: Mutex<CachedTiles>,
+}
+
+impl TileCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        TileCache {
+            ttl,
+            max_entries,
+            tiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub async fn get_or_render(&self, db: &Database, layer: TileLayer, coord: TileCoord) -> Result<Arc<Tile>, sqlx::Error> {
+        if let Some(tile) = self.get(layer, coord) {
+            return Ok(tile);
+        }
+
+        let tile = Arc::new(Tile::new(render(db, layer, coord).await?));
+        self.insert(layer, coord, tile.clone());
+        Ok(tile)
+    }
+
+    fn get(&self, layer: TileLayer, coord: TileCoord) -> Option<Arc<Tile>> {
+        let tiles = self.tiles.lock().unwrap_or_else(|e| e.into_inner());
+        let (rendered_at, tile) = tiles.get(&(layer, coord))?;
+        (rendered_at.elapsed() < self.ttl).then(|| tile.clone())
+    }
+
+    fn insert(&self, layer: TileLayer, coord: TileCoord, tile: Arc<Tile>) {
+        let mut tiles = self.tiles.lock().unwrap_or_else(|e| e.into_inner());
+        if tiles.len() >= self.max_entries {
+            let ttl = self.ttl;
+            tiles.retain(|_, (rendered_at, _)| rendered_at.elapsed() < ttl);
+        }
+        // Still full of fresh tiles: make room by dropping the oldest
+        if tiles.len() >= self.max_entries {
+            if let Some(oldest) = tiles.iter().min_by_key(|(_, (rendered_at, _))| *rendered_at).map(|(key, _)| *key) {
+                tiles.remove(&oldest);
+            }
+        }
+        tiles.insert((layer, coord), (Instant::now(), tile));
+    }
+}
+
+async fn render(db: &Database, layer: TileLayer, coord: TileCoord) -> Result<Vec<u8>, sqlx::Error> {
+    let TileCoord { z, x, y } = coord;
+    match layer {
+        TileLayer::Sensors => db.get_sensors_tile(z, x, y).await,
+        TileLayer::Readings => {
+            db.get_readings_tile(z, x, y, Utc::now() - ChronoDuration::hours(READINGS_MAX_AGE_HOURS)).await
+        }
+        TileLayer::Risk => db.get_risk_tile(z, x, y, Utc::now() - ChronoDuration::hours(RISK_MAX_AGE_HOURS)).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_addresses_and_etags() {
+        assert_eq!(TileCoord::parse(8, 41, "92.mvt").unwrap(), TileCoord { z: 8, x: 41, y: 92 });
+        assert_eq!(TileCoord::parse(0, 0, "0").unwrap(), TileCoord { z: 0, x: 0, y: 0 });
+        assert!(TileCoord::parse(2, 4, "0.mvt").is_err()); // Only 4 columns at zoom 2
+        assert!(TileCoord::parse(23, 0, "0.mvt").is_err());
+        assert!(TileCoord::parse(8, 41, "north.mvt").is_err());
+
+        let tile = Tile::new(vec![0x1a, 0x02, 0x78, 0x01]);
+        assert_eq!(tile.etag, Tile::new(vec![0x1a, 0x02, 0x78, 0x01]).etag);
+        assert_ne!(tile.etag, Tile::new(Vec::new()).etag);
+        assert!(tile.matches(&tile.etag));
+        assert!(tile.matches(&format!("\"other\", W/{}", tile.etag)));
+        assert!(!tile.matches("\"other\""));
+    }
+}
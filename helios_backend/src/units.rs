@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+/// Which unit system values are reported in. Every endpoint defaults to metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    #[default]
+    Metric,   // °C, m/s, mm
+    Imperial, // °F, mph, in
+}
+
+/// Unit labels included in API responses so clients never have to guess
+#[derive(Debug, Serialize)]
+pub struct UnitLabels {
+    pub system: UnitSystem,
+    pub temperature: &'static str,
+    pub wind_speed: &'static str,
+    pub precipitation: &'static str,
+    pub humidity: &'static str,
+    pub wind_direction: &'static str,
+}
+
+impl UnitSystem {
+    pub fn labels(self) -> UnitLabels {
+        UnitLabels {
+            system: self,
+            temperature: match self {
+                UnitSystem::Metric => "°C",
+                UnitSystem::Imperial => "°F",
+            },
+            wind_speed: match self {
+                UnitSystem::Metric => "m/s",
+                UnitSystem::Imperial => "mph",
+            },
+            precipitation: match self {
+                UnitSystem::Metric => "mm",
+                UnitSystem::Imperial => "in",
+            },
+            humidity: "%",
+            wind_direction: "°",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Temperature {
+    celsius: f64,
+}
+
+impl Temperature {
+    pub fn from_celsius(celsius: f64) -> Self {
+        Temperature { celsius }
+    }
+
+    pub fn from_fahrenheit(fahrenheit: f64) -> Self {
+        Temperature { celsius: (fahrenheit - 32.0) * 5.0 / 9.0 }
+    }
+
+    /// Parse a value stored with a unit label ("C", "F" or "K")
+    pub fn from_unit(value: f64, unit: &str) -> Option<Self> {
+        match unit {
+            "C" | "°C" => Some(Self::from_celsius(value)),
+            "F" | "°F" => Some(Self::from_fahrenheit(value)),
+            "K" => Some(Self::from_celsius(value - 273.15)),
+            _ => None,
+        }
+    }
+
+    pub fn celsius(self) -> f64 {
+        self.celsius
+    }
+
+    pub fn fahrenheit(self) -> f64 {
+        self.celsius * 9.0 / 5.0 + 32.0
+    }
+
+    pub fn in_units(self, system: UnitSystem) -> f64 {
+        match system {
+            UnitSystem::Metric => self.celsius(),
+            UnitSystem::Imperial => self.fahrenheit(),
+        }
+    }
+}
+
+const METERS_PER_SECOND_PER_MPH: f64 = 0.44704;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Speed {
+    meters_per_second: f64,
+}
+
+impl Speed {
+    pub fn from_meters_per_second(meters_per_second: f64) -> Self {
+        Speed { meters_per_second }
+    }
+
+    pub fn from_miles_per_hour(miles_per_hour: f64) -> Self {
+        Speed { meters_per_second: miles_per_hour * METERS_PER_SECOND_PER_MPH }
+    }
+
+    pub fn from_kilometers_per_hour(kilometers_per_hour: f64) -> Self {
+        Speed { meters_per_second: kilometers_per_hour / 3.6 }
+    }
+
+    /// Parse a value stored with a unit label ("m/s", "mph" or "km/h")
+    pub fn from_unit(value: f64, unit: &str) -> Option<Self> {
+        match unit {
+            "m/s" => Some(Self::from_meters_per_second(value)),
+            "mph" => Some(Self::from_miles_per_hour(value)),
+            "km/h" => Some(Self::from_kilometers_per_hour(value)),
+            _ => None,
+        }
+    }
+
+    pub fn meters_per_second(self) -> f64 {
+        self.meters_per_second
+    }
+
+    pub fn miles_per_hour(self) -> f64 {
+        self.meters_per_second / METERS_PER_SECOND_PER_MPH
+    }
+
+    pub fn kilometers_per_hour(self) -> f64 {
+        self.meters_per_second * 3.6
+    }
+
+    pub fn in_units(self, system: UnitSystem) -> f64 {
+        match system {
+            UnitSystem::Metric => self.meters_per_second(),
+            UnitSystem::Imperial => self.miles_per_hour(),
+        }
+    }
+}
+
+const MILLIMETERS_PER_INCH: f64 = 25.4;
+
+/// A depth of rain (or melted snow)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Precipitation {
+    millimeters: f64,
+}
+
+impl Precipitation {
+    pub fn from_millimeters(millimeters: f64) -> Self {
+        Precipitation { millimeters }
+    }
+
+    pub fn from_inches(inches: f64) -> Self {
+        Precipitation { millimeters: inches * MILLIMETERS_PER_INCH }
+    }
+
+    pub fn millimeters(self) -> f64 {
+        self.millimeters
+    }
+
+    pub fn inches(self) -> f64 {
+        self.millimeters / MILLIMETERS_PER_INCH
+    }
+
+    pub fn in_units(self, system: UnitSystem) -> f64 {
+        match system {
+            UnitSystem::Metric => self.millimeters(),
+            UnitSystem::Imperial => self.inches(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_temperature_conversions() {
+        assert_close(Temperature::from_celsius(100.0).fahrenheit(), 212.0);
+        assert_close(Temperature::from_fahrenheit(-40.0).celsius(), -40.0);
+        assert_close(Temperature::from_unit(293.15, "K").unwrap().celsius(), 20.0);
+        assert!(Temperature::from_unit(20.0, "furlongs").is_none());
+    }
+
+    #[test]
+    fn test_speed_conversions() {
+        assert_close(Speed::from_miles_per_hour(10.0).meters_per_second(), 4.4704);
+        assert_close(Speed::from_kilometers_per_hour(36.0).meters_per_second(), 10.0);
+        assert_close(Speed::from_meters_per_second(4.4704).in_units(UnitSystem::Imperial), 10.0);
+        assert_close(Precipitation::from_millimeters(25.4).in_units(UnitSystem::Imperial), 1.0);
+    }
+}
@@ -11,16 +11,24 @@ import matplotlib.pyplot as plt
 import seaborn as sns
 from sklearn.ensemble import RandomForestClassifier
 from sklearn.model_selection import train_test_split
-from sklearn.metrics import classification_report, confusion_matrix, accuracy_score
+from sklearn.metrics import classification_report, confusion_matrix, accuracy_score, roc_auc_score
 from sklearn.preprocessing import StandardScaler
 from sklearn.pipeline import Pipeline
 import warnings
 warnings.filterwarnings('ignore')
+import json
+import os
+import sys
+from datetime import datetime, timezone
 
 # For ONNX export
 from skl2onnx import convert_sklearn
 from skl2onnx.common.data_types import FloatTensorType
 
+# Models are exported into the backend's registry as models/<version>/
+MODEL_VERSION = sys.argv[1] if len(sys.argv) > 1 else '1.0.0'
+MODEL_DIR = os.path.join(os.environ.get('MODEL_REGISTRY_DIR', 'models'), MODEL_VERSION)
+
 print("Starting fire risk model training...")
 
 # Create synthetic fire risk dataset based on realistic weather patterns
@@ -158,11 +166,42 @@ initial_type = [('float_input', FloatTensorType([None, 5]))]
 print("\nConverting model to ONNX format...")
 onnx_model = convert_sklearn(pipeline, initial_types=initial_type)
 
-# Save the ONNX model
-with open('model.onnx', 'wb') as f:
+# Save the ONNX model and its metadata into the registry
+os.makedirs(MODEL_DIR, exist_ok=True)
+model_path = os.path.join(MODEL_DIR, 'model.onnx')
+with open(model_path, 'wb') as f:
     f.write(onnx_model.SerializeToString())
 
-print("Model successfully exported to model.onnx")
+feature_units = {
+    'temperature': '°F',
+    'humidity': '%',
+    'wind_speed': 'mph',
+    'precipitation': 'in',
+    'drought_index': '0-100',
+}
+metadata = {
+    'version': MODEL_VERSION,
+    'trained_at': datetime.now(timezone.utc).isoformat(),
+    'description': 'Random forest on synthetic weather scenarios from train_model.py',
+    'feature_schema': [
+        {
+            'name': name,
+            'unit': feature_units[name],
+            'min': float(df[name].min()),
+            'max': float(df[name].max()),
+        }
+        for name in X.columns
+    ],
+    'metrics': {
+        'accuracy': float(accuracy),
+        'roc_auc': float(roc_auc_score(y_test, y_pred_proba)),
+    },
+}
+with open(os.path.join(MODEL_DIR, 'metadata.json'), 'w') as f:
+    json.dump(metadata, f, indent=2, ensure_ascii=False)
+
+print(f"Model successfully exported to {model_path}")
+print(f"Activate it with: echo {MODEL_VERSION} > models/ACTIVE, then send SIGHUP or POST /api/admin/models/reload")
 
 # Test the exported model with sample predictions
 test_scenarios = {